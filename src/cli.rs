@@ -0,0 +1,224 @@
+use crate::{
+    Adaptive, Aov, Denoiser, Filter, Frames, Integrator, NewCamera, PixelSampler, Projection,
+    RenderSettings, Stereo, StereoLayout, ToneMapping,
+};
+
+pub const USAGE: &str = "usage: new_rt <map.json> [options]
+    --samples <n>          samples per pixel
+    --sampler <name>       stratified, jittered or halton
+    --filter <name>        box, tent, gaussian or mitchell
+    --filter-radius <r>    filter radius in pixels
+    --adaptive <n>         adaptive anti-aliasing, up to n samples per pixel
+    --threshold <t>        color difference that triggers the adaptive refinement
+    --integrator <name>    whitted or path
+    --max-depth <n>        bounces of the path tracer
+    --exposure <stops>     brighter or darker image, +1 doubles the light
+    --tone-map <name>      clamp, reinhard or aces
+    --denoise <radius>     filter the noise over the radius in pixels, 0 turns it off
+                           (N turns it on and off in the window)
+    --projection <name>    perspective, orthographic, fisheye or equirectangular
+    --stereo <layout>      left and right eyes, side-by-side or top-bottom
+    --output <file>        render once to an image (ppm, png, exr, ...) without opening a window
+    --aov <a,b,...>        depth, normal, albedo, id, direct or reflected, saved as <file>_depth.png
+    --frames <start>-<end> frames of the animation, saved as <file>_0001.png or in place of the #s
+    --fps <n>              frames per second of the animation
+    --record <path.json>   save the moves of the camera in the window
+    --replay <path.json>   render a whole recorded camera path to the --output frames";
+
+// what was given on the command line, the options win over the scene file
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub map: String,
+    pub samples: Option<usize>,
+    pub sampler: Option<PixelSampler>,
+    pub filter: Option<Filter>,
+    pub filter_radius: Option<f32>,
+    pub adaptive: Option<usize>,
+    pub threshold: Option<f32>,
+    pub integrator: Option<Integrator>,
+    pub max_depth: Option<usize>,
+    pub exposure: Option<f32>,
+    pub tone_mapping: Option<ToneMapping>,
+    pub denoise: Option<usize>,
+    pub projection: Option<Projection>,
+    pub stereo: Option<StereoLayout>,
+    pub output: Option<String>,
+    pub aovs: Option<Vec<Aov>>,
+    pub frames: Option<(usize, usize)>,
+    pub fps: Option<f32>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+}
+
+impl Options {
+    // arguments without the program name
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut map = None;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                if map.is_some() {
+                    return Err(format!("unexpected argument '{}'", arg));
+                }
+                map = Some(arg.clone());
+                continue;
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value after {}", arg))?;
+
+            match arg.as_str() {
+                "--samples" => options.samples = Some(parse_number(arg, value)?),
+                "--sampler" => options.sampler = Some(value.parse()?),
+                "--filter" => options.filter = Some(value.parse()?),
+                "--filter-radius" => options.filter_radius = Some(parse_number(arg, value)?),
+                "--adaptive" => options.adaptive = Some(parse_number(arg, value)?),
+                "--threshold" => options.threshold = Some(parse_number(arg, value)?),
+                "--integrator" => options.integrator = Some(value.parse()?),
+                "--max-depth" => options.max_depth = Some(parse_number(arg, value)?),
+                "--exposure" => options.exposure = Some(parse_number(arg, value)?),
+                "--tone-map" => options.tone_mapping = Some(value.parse()?),
+                "--denoise" => options.denoise = Some(parse_number(arg, value)?),
+                "--projection" => options.projection = Some(value.parse()?),
+                "--stereo" => options.stereo = Some(value.parse()?),
+                "--output" => options.output = Some(value.clone()),
+                "--aov" => options.aovs = Some(parse_list(value)?),
+                "--frames" => options.frames = Some(parse_range(value)?),
+                "--fps" => options.fps = Some(parse_number(arg, value)?),
+                "--record" => options.record = Some(value.clone()),
+                "--replay" => options.replay = Some(value.clone()),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+
+        options.map = map.ok_or("missing map file")?;
+        if options.fps.is_some_and(|fps| fps <= 0.) {
+            return Err("the fps must be more than 0".to_string());
+        }
+        // recording needs the window, replaying needs the files to write
+        if options.record.is_some() && options.output.is_some() {
+            return Err("--record only works in the window, without --output".to_string());
+        }
+        if options.aovs.is_some() && options.output.is_none() {
+            return Err("--aov needs --output for the buffers".to_string());
+        }
+        if options.replay.is_some() && options.output.is_none() {
+            return Err("--replay needs --output for the frames".to_string());
+        }
+        // the recording decides which frames there are
+        if options.replay.is_some() && options.frames.is_some() {
+            return Err("--frames doesn't go with --replay, the whole path is rendered".to_string());
+        }
+
+        Ok(options)
+    }
+
+    pub fn apply(&self, settings: &mut RenderSettings) {
+        if let Some(samples) = self.samples {
+            settings.samples = samples;
+        }
+        if let Some(sampler) = self.sampler {
+            settings.sampler = sampler;
+        }
+        if let Some(filter) = self.filter {
+            settings.filter = filter;
+        }
+        if let Some(radius) = self.filter_radius {
+            settings.filter_radius = Some(radius);
+        }
+        if let Some(integrator) = self.integrator {
+            settings.integrator = integrator;
+        }
+        if let Some(max_depth) = self.max_depth {
+            settings.max_depth = max_depth;
+        }
+        if let Some(exposure) = self.exposure {
+            settings.display.exposure = exposure;
+        }
+        if let Some(tone_mapping) = self.tone_mapping {
+            settings.display.tone_mapping = tone_mapping;
+        }
+        match self.denoise {
+            Some(0) => settings.denoise = None,
+            Some(radius) => {
+                settings.denoise.get_or_insert_with(Denoiser::default).radius = radius;
+            }
+            None => {}
+        }
+        if let Some(aovs) = &self.aovs {
+            settings.aovs = aovs.clone();
+        }
+        if self.adaptive.is_some() || self.threshold.is_some() {
+            let adaptive = settings.adaptive.get_or_insert_with(Adaptive::default);
+
+            if let Some(max_samples) = self.adaptive {
+                adaptive.max_samples = max_samples;
+            }
+            if let Some(threshold) = self.threshold {
+                adaptive.threshold = threshold;
+            }
+        }
+    }
+
+    pub fn apply_frames(&self, frames: &mut Frames) {
+        if let Some((start, end)) = self.frames {
+            frames.start = start;
+            frames.end = end;
+        }
+        if let Some(fps) = self.fps {
+            frames.fps = fps;
+        }
+    }
+
+    pub fn apply_camera(&self, camera: &mut NewCamera) {
+        if let Some(projection) = self.projection {
+            camera.projection = projection;
+        }
+        if let Some(layout) = self.stereo {
+            camera.stereo.get_or_insert_with(Stereo::default).layout = layout;
+        }
+    }
+}
+
+// "12-48", or a single frame
+fn parse_range(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("invalid frame range '{}', expected <start>-<end>", value);
+
+    let (start, end) = match value.split_once('-') {
+        Some((start, end)) => (start, end),
+        None => (value, value),
+    };
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+    if end < start {
+        return Err(invalid());
+    }
+
+    Ok((start, end))
+}
+
+// "depth,normal", each one only once
+fn parse_list<T>(value: &str) -> Result<Vec<T>, String>
+where
+    T: std::str::FromStr<Err = String> + PartialEq,
+{
+    let mut list = Vec::new();
+
+    for item in value.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+        let item = item.parse()?;
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+
+    Ok(list)
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, option))
+}
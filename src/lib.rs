@@ -6,15 +6,15 @@ pub const EPSILON: f32 = 0.001;
 
 // structures
 pub use structures::vector::Vector;
-pub use structures::camera::Camera;
+pub use structures::camera::{Camera, Eye, Projection, Stereo, StereoLayout};
 pub use structures::canvas::Canvas;
 pub use structures::color::Color;
-pub use structures::material::Material;
-pub use structures::light::Light;
+pub use structures::material::{Material, Pbr, Emission};
+pub use structures::light::{Light, LightKind, AreaShape, Attenuation};
 pub use structures::matrix::Matrix;
 pub use structures::ray::Ray;
 pub use structures::point::Point;
-pub use structures::object::Object;
+pub use structures::object::{Object, Motion};
 pub use structures::object::Transform;
 pub use structures::intersection::Intersection;
 pub use structures::intersection::Intersect;
@@ -29,12 +29,40 @@ pub use structures::shapes::tube::Tube;
 pub use structures::shapes::plane::Plane;
 pub use structures::shapes::cone::Cone;
 pub use structures::shapes::cylinder::Cylinder;
-pub use structures::world::World;
-pub use structures::pattern::{Pattern, Axis};
+pub use structures::shapes::custom::{
+    CustomShape, register_shape, register_shape_type, registered_shapes, create_shape,
+};
+pub use structures::bounds::Bounds;
+pub use structures::sampler::{Rng, Distribution, PixelSampler, halton};
+pub use structures::film::{Film, Filter, contrast_mask};
+pub use structures::settings::{RenderSettings, Adaptive};
+pub use structures::aov::{Aov, pass_file_name};
+pub use structures::denoise::Denoiser;
+pub use structures::tonemap::{DisplaySettings, ToneMapping, linear_to_srgb, srgb_to_linear};
+pub use structures::integrator::{Integrator, PathTracer};
+pub use structures::animation::{
+    Animatable, Interpolation, Keyframe, ObjectAnimation, LightAnimation, CameraAnimation, Frames,
+    value_at, sort_keyframes, frame_file_name,
+};
+pub use structures::camera_path::{CameraPath, CameraPose};
+pub use structures::brdf::{Bsdf, Phong, Ggx};
+pub use structures::world::{World, LightModel};
+pub use structures::environment::{Ambient, Background, EnvironmentMap};
+pub use structures::pattern::{Pattern, Axis, Paint, Combinator, Perturbation};
+pub use structures::procedural::{Procedural, Texture, ColorStop};
+pub use structures::noise::{perlin, fbm, turbulence};
+pub use structures::registry::Registry;
+pub use structures::pattern_kind::{
+    PatternKind, Stripe, Ring, Checker, Gradient, register_pattern, register_pattern_type,
+    registered_patterns, create_pattern,
+};
 
 // parsing
 pub mod parser;
-pub use parser::{get_info_map, NewCanvas};
+pub use parser::{get_info_map, NewCanvas, NewCamera};
+
+pub mod cli;
+pub use cli::Options;
 
 pub use structures::render::Renderer;
 
@@ -1,26 +1,104 @@
 
 use new_rt::get_info_map;
-use new_rt::{Canvas, Renderer};
+use new_rt::cli::USAGE;
+use new_rt::{frame_file_name, pass_file_name, CameraPath, Canvas, Frames, Options, Renderer};
 
 use std::env;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 2 {
-        eprintln!("Error: wrong number of argument, expeted 1 get {}", args.len() - 1);
-        std::process::exit(1);
-    }
+    let options = match Options::parse(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {}\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
 
-    let map_name = String::from(args[1].clone());
-    match get_info_map(&map_name) {
-        Ok (info_map) => {
+    match get_info_map(&options.map) {
+        Ok (mut info_map) => {
             eprintln!("{:?}", info_map);
+            options.apply(&mut info_map.render);
+            options.apply_camera(&mut info_map.camera);
+            options.apply_frames(&mut info_map.frames);
+            let frames = info_map.frames;
             let canvas = Canvas::new(info_map.canvas);
-            match Renderer::new(canvas, info_map.world) {
+
+            if let Some(output) = &options.output {
+                let mut renderer = Renderer::headless(canvas, info_map.world.clone());
+                renderer.settings = info_map.render;
+
+                // the frames cover the recorded flight instead of the scene's range
+                let path = match &options.replay {
+                    Some(file) => match CameraPath::load(file) {
+                        Ok(path) => Some(path),
+                        Err(e) => {
+                            eprintln!("Error loading the camera path: {}", e);
+                            std::process::exit(1);
+                        }
+                    },
+                    None => None,
+                };
+                let frames = match &path {
+                    Some(path) => {
+                        let end = (path.duration() * frames.fps).floor() as usize;
+                        Frames::new(0, end, frames.fps)
+                    }
+                    None => frames,
+                };
+
+                for frame in frames.start..=frames.end {
+                    renderer.set_frame(&info_map.world, &info_map.camera, &frames, frame);
+                    if let Some(pose) = path.as_ref().and_then(|p| p.pose_at(frames.time(frame))) {
+                        renderer.camera.set_pose(&pose);
+                    }
+                    renderer.update_image();
+
+                    let file = if frames.len() > 1 || output.contains('#') {
+                        frame_file_name(output, frame)
+                    } else {
+                        output.clone()
+                    };
+                    if let Err(e) = renderer.canvas.save(&file, &renderer.settings.display) {
+                        eprintln!("Error saving the image: {}", e);
+                        std::process::exit(1);
+                    }
+                    eprintln!("frame {} saved to {}", frame, file);
+
+                    if !renderer.settings.aovs.is_empty() {
+                        renderer.render_passes();
+                    }
+                    for (aov, pass) in &renderer.passes {
+                        let pass_file = pass_file_name(&file, *aov);
+                        if let Err(e) = aov.save(pass, &pass_file, &renderer.settings.display) {
+                            eprintln!("Error saving the {} buffer: {}", aov.name(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                return;
+            }
+
+            match Renderer::new(canvas, info_map.world.clone()) {
                 Ok(mut renderer) => {
+                    renderer.settings = info_map.render;
+                    if !renderer.settings.aovs.is_empty() {
+                        eprintln!("Warning: the aovs of the scene are only saved with --output");
+                    }
+                    renderer.set_frame(&info_map.world, &info_map.camera, &frames, frames.start);
+                    if options.record.is_some() {
+                        renderer.recording = Some(CameraPath::new());
+                    }
                     renderer.update_image();
                     let _ = renderer.render();
+
+                    if let (Some(file), Some(recording)) = (&options.record, &renderer.recording) {
+                        match recording.save(file) {
+                            Ok(()) => eprintln!("{} camera poses saved to {}", recording.len(), file),
+                            Err(e) => eprintln!("Error saving the camera path: {}", e),
+                        }
+                    }
                 }
                 Err(e) => {
                     eprintln!("Error from renderer creation: {:?}", e);
@@ -1,13 +1,21 @@
 
 use std::fs;
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
-use crate::World;
+use crate::structures::animation::value_at;
+use crate::{CameraAnimation, Frames, Point, Projection, RenderSettings, Stereo, Vector, World};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct InfoMap {
     pub canvas: NewCanvas,
     pub world: World,
+    #[serde(default)]
+    pub render: RenderSettings,
+    #[serde(default)]
+    pub camera: NewCamera,
+    #[serde(default)]
+    pub frames: Frames,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -16,8 +24,114 @@ pub struct NewCanvas {
     pub height: usize,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewCamera {
+    #[serde(default = "default_camera_position")]
+    pub position: Point,
+    #[serde(default = "default_camera_direction")]
+    pub direction: Vector,
+    #[serde(default = "default_fov")]
+    pub fov: f32, // degrees
+    #[serde(default)]
+    pub aperture: f32,
+    #[serde(default = "default_focus_distance")]
+    pub focus_distance: f32,
+    #[serde(default)]
+    pub autofocus: bool,
+    #[serde(default)]
+    pub projection: Projection,
+    #[serde(default = "default_view_height")]
+    pub view_height: f32, // orthographic only
+    #[serde(default)]
+    pub stereo: Option<Stereo>,
+    #[serde(default)]
+    pub animation: Option<CameraAnimation>,
+    #[serde(default)]
+    pub shutter_open: f32,
+    #[serde(default)]
+    pub shutter_close: f32,
+}
+
+impl Default for NewCamera {
+    fn default() -> Self {
+        Self {
+            position: default_camera_position(),
+            direction: default_camera_direction(),
+            fov: default_fov(),
+            aperture: 0.,
+            focus_distance: default_focus_distance(),
+            autofocus: false,
+            projection: Projection::Perspective,
+            view_height: default_view_height(),
+            stereo: None,
+            animation: None,
+            shutter_open: 0.,
+            shutter_close: 0.,
+        }
+    }
+}
+
+impl NewCamera {
+    // the camera as its keyframes say at a time in seconds
+    pub fn at_time(&self, time: f32) -> NewCamera {
+        let mut camera = self.clone();
+
+        if let Some(animation) = &self.animation {
+            camera.position = value_at(&animation.position, time).unwrap_or(self.position);
+            camera.direction = value_at(&animation.direction, time).unwrap_or(self.direction);
+            camera.fov = value_at(&animation.fov, time).unwrap_or(self.fov);
+            camera.focus_distance =
+                value_at(&animation.focus_distance, time).unwrap_or(self.focus_distance);
+        }
+
+        camera
+    }
+}
+
+fn default_camera_position() -> Point {
+    Point::new(0., 0., 10.)
+}
+
+fn default_camera_direction() -> Vector {
+    Vector::new(0., 0., -1.)
+}
+
+fn default_fov() -> f32 {
+    45.
+}
+
+fn default_focus_distance() -> f32 {
+    10.
+}
+
+fn default_view_height() -> f32 {
+    10.
+}
+
 pub fn get_info_map(file_name: &String) -> Result<InfoMap, Box<dyn std::error::Error>> {
     let json_str = fs::read_to_string(file_name)?;
-    let root: InfoMap = serde_json::from_str(&json_str)?;
+    let mut root: InfoMap = serde_json::from_str(&json_str)?;
+    root.world.load_resources(Path::new(file_name).parent())?;
+
+    // the same check as --fps, the frame times divide by it
+    if root.frames.fps <= 0. {
+        return Err("the fps must be more than 0".into());
+    }
+
+    // the keyframes can be written in any order
+    for object in &mut root.world.objects {
+        if let Some(animation) = &mut object.animation {
+            animation.sort();
+        }
+    }
+    for light in &mut root.world.lights {
+        if let Some(animation) = &mut light.animation {
+            animation.sort();
+        }
+    }
+    if let Some(animation) = &mut root.camera.animation {
+        animation.sort();
+    }
+
     Ok(root)
 }
@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Point, Vector};
+
+// values that can go smoothly from one keyframe to the next
+pub trait Animatable: Copy {
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Animatable for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Animatable for Point {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Animatable for Vector {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+// how a keyframe goes to the next one
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    Step, // holds the value until the next keyframe
+    // easing curve from (0, 0) to (1, 1) with the handles (x1, y1) and (x2, y2),
+    // like the css cubic-bezier
+    Bezier([f32; 4]),
+}
+
+impl Interpolation {
+    // the classic ease in and out
+    pub fn ease() -> Self {
+        Interpolation::Bezier([0.42, 0., 0.58, 1.])
+    }
+
+    // progress between two keyframes for a part t of the time between them
+    pub fn progress(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Interpolation::Linear => t,
+            Interpolation::Step => 0.,
+            Interpolation::Bezier([x1, y1, x2, y2]) => {
+                // x keeps growing with the handles in [0, 1], find the point of
+                // the curve at t by bisection
+                let (x1, x2) = (x1.clamp(0., 1.), x2.clamp(0., 1.));
+                let (mut low, mut high) = (0., 1.);
+                let mut s = t;
+
+                for _ in 0..32 {
+                    if cubic(x1, x2, s) < t {
+                        low = s;
+                    } else {
+                        high = s;
+                    }
+                    s = (low + high) / 2.;
+                }
+
+                cubic(*y1, *y2, s)
+            }
+        }
+    }
+}
+
+// one coordinate of the bezier curve from 0 to 1 with the handles a and b
+fn cubic(a: f32, b: f32, s: f32) -> f32 {
+    let r = 1. - s;
+
+    3. * r * r * s * a + 3. * r * s * s * b + s * s * s
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Keyframe<T> {
+    pub time: f32, // seconds
+    pub value: T,
+    #[serde(default)]
+    pub interpolation: Interpolation, // toward the next keyframe
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(time: f32, value: T) -> Self {
+        Self {
+            time,
+            value,
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+
+        self
+    }
+}
+
+// value of a track of keyframes in time order, held before the first and after
+// the last, None when there is no keyframe. the keys must be sorted first, with
+// sort_keyframes, get_info_map does it for the tracks of a map
+pub fn value_at<T: Animatable>(keys: &[Keyframe<T>], time: f32) -> Option<T> {
+    debug_assert!(keys.windows(2).all(|pair| pair[0].time <= pair[1].time));
+    let next = keys.partition_point(|key| key.time <= time);
+
+    if next == 0 {
+        return keys.first().map(|key| key.value);
+    }
+    let key = &keys[next - 1];
+    let following = match keys.get(next) {
+        Some(following) => following,
+        None => return Some(key.value),
+    };
+
+    let progress = key
+        .interpolation
+        .progress((time - key.time) / (following.time - key.time));
+
+    Some(key.value.lerp(following.value, progress))
+}
+
+// puts a track in time order as value_at wants it, the keyframes at the same
+// time keep the order they were written in
+pub fn sort_keyframes<T>(keys: &mut [Keyframe<T>]) {
+    keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+}
+
+// what moves on an object, the tracks without keyframes leave it as it is
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ObjectAnimation {
+    #[serde(default)]
+    pub position: Vec<Keyframe<Point>>,
+    #[serde(default)]
+    pub pitch: Vec<Keyframe<f32>>,
+    #[serde(default)]
+    pub yaw: Vec<Keyframe<f32>>,
+    #[serde(default)]
+    pub roll: Vec<Keyframe<f32>>,
+    #[serde(default)]
+    pub scale: Vec<Keyframe<Vector>>,
+    #[serde(default)]
+    pub color: Vec<Keyframe<Color>>, // of the material
+}
+
+impl ObjectAnimation {
+    pub fn sort(&mut self) {
+        sort_keyframes(&mut self.position);
+        sort_keyframes(&mut self.pitch);
+        sort_keyframes(&mut self.yaw);
+        sort_keyframes(&mut self.roll);
+        sort_keyframes(&mut self.scale);
+        sort_keyframes(&mut self.color);
+    }
+
+    // true when the object changes place, not only color
+    pub fn moves(&self) -> bool {
+        !(self.position.is_empty()
+            && self.pitch.is_empty()
+            && self.yaw.is_empty()
+            && self.roll.is_empty()
+            && self.scale.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LightAnimation {
+    #[serde(default)]
+    pub position: Vec<Keyframe<Point>>,
+    #[serde(default)]
+    pub intensity: Vec<Keyframe<Color>>,
+}
+
+impl LightAnimation {
+    pub fn sort(&mut self) {
+        sort_keyframes(&mut self.position);
+        sort_keyframes(&mut self.intensity);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CameraAnimation {
+    #[serde(default)]
+    pub position: Vec<Keyframe<Point>>,
+    #[serde(default)]
+    pub direction: Vec<Keyframe<Vector>>,
+    #[serde(default)]
+    pub fov: Vec<Keyframe<f32>>, // degrees
+    #[serde(default)]
+    pub focus_distance: Vec<Keyframe<f32>>,
+}
+
+impl CameraAnimation {
+    pub fn sort(&mut self) {
+        sort_keyframes(&mut self.position);
+        sort_keyframes(&mut self.direction);
+        sort_keyframes(&mut self.fov);
+        sort_keyframes(&mut self.focus_distance);
+    }
+}
+
+// the frames to render, the scene file can give them and the command line
+// override them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Frames {
+    #[serde(default)]
+    pub start: usize,
+    #[serde(default)]
+    pub end: usize, // included
+    #[serde(default = "default_fps")]
+    pub fps: f32,
+}
+
+impl Default for Frames {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: 0,
+            fps: default_fps(),
+        }
+    }
+}
+
+fn default_fps() -> f32 {
+    24.
+}
+
+impl Frames {
+    pub fn new(start: usize, end: usize, fps: f32) -> Self {
+        Self { start, end, fps }
+    }
+
+    pub fn len(&self) -> usize {
+        (self.end + 1).saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // seconds since the start of the animation
+    pub fn time(&self, frame: usize) -> f32 {
+        frame as f32 / self.fps
+    }
+
+    pub fn duration(&self) -> f32 {
+        1. / self.fps
+    }
+}
+
+// name of the image of a frame: the #s of the pattern are replaced by the
+// frame number padded with zeros, "_0042" goes before the extension without them
+pub fn frame_file_name(pattern: &str, frame: usize) -> String {
+    if let Some(start) = pattern.rfind('#') {
+        let first = pattern[..=start].trim_end_matches('#').len();
+        let width = start + 1 - first;
+
+        return format!(
+            "{}{:0width$}{}",
+            &pattern[..first],
+            frame,
+            &pattern[start + 1..],
+            width = width
+        );
+    }
+
+    match pattern.rfind('.') {
+        Some(dot) if !pattern[dot..].contains('/') => {
+            format!("{}_{:04}{}", &pattern[..dot], frame, &pattern[dot..])
+        }
+        _ => format!("{}_{:04}", pattern, frame),
+    }
+}
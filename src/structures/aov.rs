@@ -0,0 +1,140 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Canvas, Color, DisplaySettings, NewCanvas};
+
+// a buffer rendered next to the image for the compositing
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    Depth,     // distance from the camera to the first hit, infinite where nothing is hit
+    Normal,    // world normal of the first hit
+    Albedo,    // color of the material at the first hit, the background elsewhere
+    ObjectId,  // index of the object in the scene plus one, 0 for nothing
+    Direct,    // light coming straight from the lights and the emitters
+    Reflected, // light after one bounce or more
+}
+
+impl Aov {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::ObjectId => "id",
+            Aov::Direct => "direct",
+            Aov::Reflected => "reflected",
+        }
+    }
+
+    // light passes are sampled like the image but left out of the denoiser, the
+    // others once at each pixel center
+    pub fn is_light(&self) -> bool {
+        matches!(self, Aov::Direct | Aov::Reflected)
+    }
+
+    // the float values as they are in exr files, something to look at otherwise:
+    // the light passes like the image, the albedo linear as the materials give it
+    pub fn save(
+        &self,
+        pass: &Canvas,
+        filename: &str,
+        display: &DisplaySettings,
+    ) -> Result<(), String> {
+        let float = Path::new(filename)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exr"));
+        if float || self.is_light() {
+            return pass.save(filename, display);
+        }
+
+        let raw = DisplaySettings {
+            srgb: false,
+            ..DisplaySettings::default()
+        };
+        self.preview(pass).save(filename, &raw)
+    }
+
+    // depth white at the closest point and fading with the distance (an
+    // infinite floor would squeeze a linear scale), normals from [-1, 1] to
+    // [0, 1], one color per object
+    fn preview(&self, pass: &Canvas) -> Canvas {
+        let mut preview = Canvas::new(NewCanvas {
+            width: pass.width,
+            height: pass.height,
+        });
+        let mut near = f32::INFINITY;
+        for y in 0..pass.height {
+            for x in 0..pass.width {
+                near = near.min(pass.at(x, y).red().max(0.));
+            }
+        }
+
+        for y in 0..pass.height {
+            for x in 0..pass.width {
+                let value = pass.at(x, y);
+                let color = match self {
+                    Aov::Depth if value.red() > 0. && near.is_finite() => {
+                        let gray = (near / value.red()).min(1.);
+                        Color::new(gray, gray, gray)
+                    }
+                    Aov::Depth => Color::BLACK,
+                    Aov::Normal => (value + Color::WHITE) * 0.5,
+                    Aov::ObjectId => id_color(value.red() as usize),
+                    _ => value,
+                };
+                preview.write(x, y, color);
+            }
+        }
+
+        preview
+    }
+}
+
+// far apart hues for the following indices
+fn id_color(id: usize) -> Color {
+    if id == 0 {
+        return Color::BLACK;
+    }
+
+    let hue = (id as f32 * 0.618034).fract() * 6.;
+    let x = 1. - (hue % 2. - 1.).abs();
+    match hue as usize {
+        0 => Color::new(1., x, 0.),
+        1 => Color::new(x, 1., 0.),
+        2 => Color::new(0., 1., x),
+        3 => Color::new(0., x, 1.),
+        4 => Color::new(x, 0., 1.),
+        _ => Color::new(1., 0., x),
+    }
+}
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "depth" | "z" => Ok(Aov::Depth),
+            "normal" | "normals" => Ok(Aov::Normal),
+            "albedo" => Ok(Aov::Albedo),
+            "id" | "object-id" => Ok(Aov::ObjectId),
+            "direct" => Ok(Aov::Direct),
+            "reflected" | "indirect" => Ok(Aov::Reflected),
+            _ => Err(format!(
+                "unknown pass '{}', expected depth, normal, albedo, id, direct or reflected",
+                s
+            )),
+        }
+    }
+}
+
+// "shot_0001.png" becomes "shot_0001_depth.png"
+pub fn pass_file_name(file: &str, aov: Aov) -> String {
+    match file.rfind('.') {
+        Some(dot) if !file[dot..].contains('/') => {
+            format!("{}_{}{}", &file[..dot], aov.name(), &file[dot..])
+        }
+        _ => format!("{}_{}", file, aov.name()),
+    }
+}
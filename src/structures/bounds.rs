@@ -0,0 +1,88 @@
+use std::mem::swap;
+
+use crate::{Point, Ray};
+
+use serde::{Deserialize, Serialize};
+
+// axis aligned box, used to skip the costly intersections when the ray is far away
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    pub fn infinite() -> Self {
+        Self {
+            min: Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Point::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.min.x.is_finite()
+            && self.min.y.is_finite()
+            && self.min.z.is_finite()
+            && self.max.x.is_finite()
+            && self.max.y.is_finite()
+            && self.max.z.is_finite()
+    }
+
+    pub fn corners(&self) -> [Point; 8] {
+        let (a, b) = (self.min, self.max);
+
+        [
+            Point::new(a.x, a.y, a.z),
+            Point::new(b.x, a.y, a.z),
+            Point::new(a.x, b.y, a.z),
+            Point::new(b.x, b.y, a.z),
+            Point::new(a.x, a.y, b.z),
+            Point::new(b.x, a.y, b.z),
+            Point::new(a.x, b.y, b.z),
+            Point::new(b.x, b.y, b.z),
+        ]
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+            && point.z >= self.min.z
+            && point.z <= self.max.z
+    }
+
+    // slab test, true when the ray goes through the box in front of its origin
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let check_axis = |o: f32, d: f32, min: f32, max: f32| -> (f32, f32) {
+            if d == 0. {
+                return if o < min || o > max {
+                    (f32::INFINITY, f32::NEG_INFINITY)
+                } else {
+                    (f32::NEG_INFINITY, f32::INFINITY)
+                };
+            }
+
+            let mut tmin = (min - o) / d;
+            let mut tmax = (max - o) / d;
+            if tmin > tmax {
+                swap(&mut tmin, &mut tmax);
+            }
+
+            (tmin, tmax)
+        };
+
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+
+        let tmin = xtmin.max(ytmin.max(ztmin));
+        let tmax = xtmax.min(ytmax.min(ztmax));
+
+        tmin <= tmax && tmax >= 0.
+    }
+}
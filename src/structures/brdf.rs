@@ -0,0 +1,239 @@
+use std::f32::consts::PI;
+
+use crate::structures::sampler::{basis, power_cosine, power_cosine_pdf};
+use crate::{Color, Object, Pbr, Point, Vector};
+
+// the two material models behind the same interface
+#[derive(Debug, Clone, Copy)]
+pub enum Bsdf {
+    Phong(Phong),
+    Ggx(Ggx),
+}
+
+impl Bsdf {
+    pub fn from_object(obj: &Object, point: &Point) -> Self {
+        match &obj.material.pbr {
+            Some(pbr) => Bsdf::Ggx(Ggx::new(obj.color_at(point), pbr)),
+            None => Bsdf::Phong(Phong::from_object(obj, point)),
+        }
+    }
+
+    pub fn eval(&self, wi: &Vector, wo: &Vector, normal: &Vector) -> Color {
+        match self {
+            Bsdf::Phong(phong) => phong.eval(wi, wo, normal),
+            Bsdf::Ggx(ggx) => ggx.eval(wi, wo, normal),
+        }
+    }
+
+    pub fn sample(&self, wo: &Vector, normal: &Vector, u: f32, v: f32, w: f32) -> Option<Vector> {
+        match self {
+            Bsdf::Phong(phong) => phong.sample(wo, normal, u, v, w),
+            Bsdf::Ggx(ggx) => ggx.sample(wo, normal, u, v, w),
+        }
+    }
+
+    pub fn pdf(&self, wi: &Vector, wo: &Vector, normal: &Vector) -> f32 {
+        match self {
+            Bsdf::Phong(phong) => phong.pdf(wi, wo, normal),
+            Bsdf::Ggx(ggx) => ggx.pdf(wi, wo, normal),
+        }
+    }
+}
+
+// energy conserving version of the phong material, used when the light comes
+// from every direction (environment, indirect bounces) instead of a few points
+#[derive(Debug, Clone, Copy)]
+pub struct Phong {
+    pub albedo: Color,
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+impl Phong {
+    pub fn new(albedo: Color, specular: f32, shininess: f32) -> Self {
+        Self {
+            albedo,
+            specular,
+            shininess,
+        }
+    }
+
+    pub fn from_object(obj: &Object, point: &Point) -> Self {
+        let material = &obj.material;
+
+        Phong::new(
+            obj.color_at(point) * material.diffuse,
+            material.specular,
+            material.shininess,
+        )
+    }
+
+    // light going out toward wo for each unit of light coming from wi (both away from the surface)
+    pub fn eval(&self, wi: &Vector, wo: &Vector, normal: &Vector) -> Color {
+        if wi.dot(normal) <= 0. || wo.dot(normal) <= 0. {
+            return Color::BLACK;
+        }
+
+        let diffuse = self.albedo * (1. / PI);
+        let cos_alpha = (-*wi).reflect(normal).dot(wo);
+        if cos_alpha <= 0. || self.specular <= 0. {
+            return diffuse;
+        }
+
+        let lobe = self.specular * (self.shininess + 2.) / (2. * PI) * cos_alpha.powf(self.shininess);
+        diffuse + Color::WHITE * lobe
+    }
+
+    // chance of sampling the glossy lobe rather than the diffuse one
+    fn specular_weight(&self) -> f32 {
+        let diffuse = self.albedo.luminance();
+        if diffuse + self.specular <= 0. {
+            return 0.;
+        }
+
+        self.specular / (diffuse + self.specular)
+    }
+
+    // direction of incoming light following the shape of the material, None under the surface
+    pub fn sample(&self, wo: &Vector, normal: &Vector, u: f32, v: f32, w: f32) -> Option<Vector> {
+        let wi = if w < self.specular_weight() {
+            power_cosine(&(-*wo).reflect(normal), self.shininess, u, v)
+        } else {
+            power_cosine(normal, 1., u, v)
+        };
+
+        if wi.dot(normal) <= 0. {
+            return None;
+        }
+
+        Some(wi)
+    }
+
+    pub fn pdf(&self, wi: &Vector, wo: &Vector, normal: &Vector) -> f32 {
+        if wi.dot(normal) <= 0. {
+            return 0.;
+        }
+
+        let ks = self.specular_weight();
+        let diffuse = power_cosine_pdf(normal, 1., wi);
+        let specular = power_cosine_pdf(&(-*wo).reflect(normal), self.shininess, wi);
+
+        (1. - ks) * diffuse + ks * specular
+    }
+}
+
+// below this the highlight gets so thin that the floats can't follow
+const MIN_ALPHA: f32 = 0.002;
+
+// microfacets: ggx distribution, smith shadowing and schlick fresnel, with a
+// lambert layer under the reflection of the non metals
+#[derive(Debug, Clone, Copy)]
+pub struct Ggx {
+    pub diffuse: Color,
+    pub f0: Color, // reflected at normal incidence
+    pub alpha: f32,
+}
+
+impl Ggx {
+    pub fn new(base_color: Color, pbr: &Pbr) -> Self {
+        let metallic = pbr.metallic.clamp(0., 1.);
+        let dielectric = Color::WHITE * pbr.reflectance;
+
+        Self {
+            diffuse: base_color * (1. - metallic),
+            f0: dielectric * (1. - metallic) + base_color * metallic,
+            alpha: (pbr.roughness * pbr.roughness).max(MIN_ALPHA),
+        }
+    }
+
+    // density of the microfacets facing h
+    fn distribution(&self, n_dot_h: f32) -> f32 {
+        let a2 = self.alpha * self.alpha;
+        let d = n_dot_h * n_dot_h * (a2 - 1.) + 1.;
+
+        a2 / (PI * d * d)
+    }
+
+    // part of the microfacets seen from a direction, not hidden by the others
+    fn smith(&self, n_dot_v: f32) -> f32 {
+        let a2 = self.alpha * self.alpha;
+
+        2. * n_dot_v / (n_dot_v + (a2 + (1. - a2) * n_dot_v * n_dot_v).sqrt())
+    }
+
+    fn fresnel(&self, cos: f32) -> Color {
+        self.f0 + (Color::WHITE - self.f0) * (1. - cos).clamp(0., 1.).powi(5)
+    }
+
+    pub fn eval(&self, wi: &Vector, wo: &Vector, normal: &Vector) -> Color {
+        let n_dot_i = wi.dot(normal);
+        let n_dot_o = wo.dot(normal);
+        if n_dot_i <= 0. || n_dot_o <= 0. {
+            return Color::BLACK;
+        }
+
+        let h = (*wi + *wo).normalize();
+        let fresnel = self.fresnel(wi.dot(&h));
+        let d = self.distribution(normal.dot(&h).max(0.));
+        let g = self.smith(n_dot_i) * self.smith(n_dot_o);
+
+        let specular = fresnel * (d * g / (4. * n_dot_i * n_dot_o));
+        // what the reflection leaves goes into the diffuse layer
+        let diffuse = self.diffuse * (Color::WHITE - fresnel) * (1. / PI);
+
+        diffuse + specular
+    }
+
+    // chance of sampling the reflection rather than the diffuse layer
+    fn specular_weight(&self, wo: &Vector, normal: &Vector) -> f32 {
+        let specular = self.fresnel(wo.dot(normal)).luminance();
+        let diffuse = self.diffuse.luminance();
+        if diffuse <= 0. {
+            return 1.;
+        }
+
+        (specular / (specular + diffuse)).clamp(0.1, 0.9)
+    }
+
+    pub fn sample(&self, wo: &Vector, normal: &Vector, u: f32, v: f32, w: f32) -> Option<Vector> {
+        let wi = if w < self.specular_weight(wo, normal) {
+            // microfacet normal following the distribution, then mirror around it
+            let a2 = self.alpha * self.alpha;
+            let cos_theta = ((1. - u) / (1. + (a2 - 1.) * u)).sqrt();
+            let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+            let phi = 2. * PI * v;
+            let (tangent, bitangent) = basis(normal);
+            let h = tangent * (sin_theta * phi.cos())
+                + bitangent * (sin_theta * phi.sin())
+                + *normal * cos_theta;
+
+            h * (2. * wo.dot(&h)) - *wo
+        } else {
+            power_cosine(normal, 1., u, v)
+        };
+
+        if wi.dot(normal) <= 0. {
+            return None;
+        }
+
+        Some(wi.normalize())
+    }
+
+    pub fn pdf(&self, wi: &Vector, wo: &Vector, normal: &Vector) -> f32 {
+        if wi.dot(normal) <= 0. {
+            return 0.;
+        }
+
+        let ks = self.specular_weight(wo, normal);
+        let h = (*wi + *wo).normalize();
+        let o_dot_h = wo.dot(&h);
+        let specular = if o_dot_h > 0. {
+            let n_dot_h = normal.dot(&h).max(0.);
+            self.distribution(n_dot_h) * n_dot_h / (4. * o_dot_h)
+        } else {
+            0.
+        };
+
+        (1. - ks) * power_cosine_pdf(normal, 1., wi) + ks * specular
+    }
+}
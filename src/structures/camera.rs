@@ -1,6 +1,118 @@
 
+use std::f32::consts::PI;
 use std::ops::{Add, Sub, Mul};
-use crate::{Point, Vector, Direction};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::structures::sampler::concentric_disk;
+use crate::{CameraPose, Point, Vector, Direction, NewCamera, Ray};
+
+// how the camera turns the points of the image into rays
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+    #[default]
+    Perspective,
+    Orthographic,    // parallel rays, no perspective, for technical drawings
+    Fisheye,         // equidistant, the fov is the angle across the circle
+    Equirectangular, // the whole sphere around the camera, for 360 panoramas
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "perspective" => Ok(Projection::Perspective),
+            "orthographic" | "ortho" => Ok(Projection::Orthographic),
+            "fisheye" => Ok(Projection::Fisheye),
+            "equirectangular" | "360" => Ok(Projection::Equirectangular),
+            _ => Err(format!(
+                "unknown projection '{}', expected perspective, orthographic, fisheye or equirectangular",
+                s
+            )),
+        }
+    }
+}
+
+// what each eye sees is put in one half of the image
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum StereoLayout {
+    #[default]
+    SideBySide, // left eye on the left
+    TopBottom,  // left eye on top
+}
+
+impl FromStr for StereoLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "side-by-side" | "sbs" => Ok(StereoLayout::SideBySide),
+            "top-bottom" | "tb" => Ok(StereoLayout::TopBottom),
+            _ => Err(format!(
+                "unknown stereo layout '{}', expected side-by-side or top-bottom",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+// two views for a vr headset, a pair of eyes around the camera position
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Stereo {
+    #[serde(default)]
+    pub layout: StereoLayout,
+    #[serde(default = "default_eye_separation")]
+    pub eye_separation: f32, // interocular distance, in world units
+    #[serde(default = "default_convergence")]
+    pub convergence: f32, // distance where both eyes see the same thing, no parallax there
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self {
+            layout: StereoLayout::default(),
+            eye_separation: default_eye_separation(),
+            convergence: default_convergence(),
+        }
+    }
+}
+
+impl Stereo {
+    pub fn new(layout: StereoLayout) -> Self {
+        Self {
+            layout,
+            ..Self::default()
+        }
+    }
+
+    pub fn eye_separation(mut self, eye_separation: f32) -> Self {
+        self.eye_separation = eye_separation;
+
+        self
+    }
+
+    pub fn convergence(mut self, convergence: f32) -> Self {
+        self.convergence = convergence;
+
+        self
+    }
+}
+
+fn default_eye_separation() -> f32 {
+    0.064
+}
+
+fn default_convergence() -> f32 {
+    10.
+}
 
 fn lerp<T>(start: T, end: T, t: f32) -> T
 where
@@ -21,6 +133,17 @@ pub struct Camera {
 
     pub pitch: f32,
     pub yaw: f32,
+
+    pub aperture: f32,       // radius of the lens, 0 for a pinhole where everything is sharp
+    pub focus_distance: f32, // distance of the sharp plane, along the view direction
+    pub autofocus: bool,     // focus on what is under the center of the screen
+
+    pub projection: Projection,
+    pub stereo: Option<Stereo>,
+    pub view_height: f32, // size of what the orthographic camera sees, in world units
+
+    pub shutter_open: f32,  // part of the motion seen in a frame, from 0 to 1
+    pub shutter_close: f32, // equal to the opening, nothing moves
 }
 
 impl Camera {
@@ -45,6 +168,236 @@ impl Camera {
             far,
             pitch,
             yaw,
+            aperture: 0.,
+            focus_distance: 10.,
+            autofocus: false,
+            projection: Projection::Perspective,
+            stereo: None,
+            view_height: 10.,
+            shutter_open: 0.,
+            shutter_close: 0.,
+        }
+    }
+
+    pub fn from_settings(settings: &NewCamera, aspect: f32) -> Camera {
+        Camera::new(
+            settings.position,
+            settings.direction,
+            aspect,
+            settings.fov.to_radians(),
+            0.1,
+            100.,
+        )
+        .aperture(settings.aperture)
+        .focus_distance(settings.focus_distance)
+        .autofocus(settings.autofocus)
+        .shutter(settings.shutter_open, settings.shutter_close)
+        .projection(settings.projection)
+        .view_height(settings.view_height)
+        .stereo(settings.stereo)
+    }
+
+    pub fn stereo(mut self, stereo: Option<Stereo>) -> Self {
+        self.stereo = stereo;
+
+        self
+    }
+
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+
+        self
+    }
+
+    pub fn view_height(mut self, view_height: f32) -> Self {
+        self.view_height = view_height;
+
+        self
+    }
+
+    pub fn aperture(mut self, aperture: f32) -> Self {
+        self.aperture = aperture.max(0.);
+
+        self
+    }
+
+    pub fn focus_distance(mut self, focus_distance: f32) -> Self {
+        self.focus_distance = focus_distance.max(self.near);
+
+        self
+    }
+
+    pub fn autofocus(mut self, autofocus: bool) -> Self {
+        self.autofocus = autofocus;
+
+        self
+    }
+
+    pub fn shutter(mut self, open: f32, close: f32) -> Self {
+        self.shutter_open = open.clamp(0., 1.);
+        self.shutter_close = close.clamp(self.shutter_open, 1.);
+
+        self
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        CameraPose::new(self.position, self.pitch, self.yaw)
+    }
+
+    // jumps to the pose, without the smoothing of the moves from the keyboard
+    pub fn set_pose(&mut self, pose: &CameraPose) {
+        self.position = pose.position;
+        self.target = pose.position;
+        self.pitch = pose.pitch;
+        self.yaw = pose.yaw;
+    }
+
+    // moment of the shutter for a random number u in [0, 1)
+    pub fn shutter_time(&self, u: f32) -> f32 {
+        self.shutter_open + (self.shutter_close - self.shutter_open) * u
+    }
+
+    // move the sharp plane to the point
+    pub fn focus_on(&mut self, point: &Point) {
+        let distance = (*point - self.position).dot(&self.direction());
+
+        if distance > self.near {
+            self.focus_distance = distance;
+        }
+    }
+
+    // the pinhole ray, moved to a point (u, v) of the lens and bent toward the
+    // same point of the sharp plane
+    pub fn lens_ray(&self, pinhole: &Ray, u: f32, v: f32) -> Ray {
+        if self.aperture <= 0. {
+            return *pinhole;
+        }
+
+        let forward = self.direction();
+        let direction = pinhole.direction.normalize();
+        let cos = direction.dot(&forward);
+        if cos <= 0. {
+            return *pinhole;
+        }
+
+        let focus = pinhole.origin + direction * (self.focus_distance / cos);
+
+        let right = forward.cross(&Vector::new(0., 1., 0.)).normalize();
+        let up = right.cross(&forward);
+        let (x, y) = concentric_disk(u, v);
+        let origin = pinhole.origin + right * (x * self.aperture) + up * (y * self.aperture);
+
+        Ray::new(origin, (focus - origin).normalize())
+    }
+
+    // ray through the point (u, v) of the image, from (0, 0) at the top left to
+    // (1, 1) at the bottom right, None outside of the fisheye circle
+    pub fn ray(&self, u: f32, v: f32) -> Option<Ray> {
+        let stereo = match &self.stereo {
+            Some(stereo) => stereo,
+            None => return self.view_ray(u, v),
+        };
+
+        match stereo.layout {
+            StereoLayout::SideBySide if u < 0.5 => self.eye_ray(Eye::Left, u * 2., v),
+            StereoLayout::SideBySide => self.eye_ray(Eye::Right, u * 2. - 1., v),
+            StereoLayout::TopBottom if v < 0.5 => self.eye_ray(Eye::Left, u, v * 2.),
+            StereoLayout::TopBottom => self.eye_ray(Eye::Right, u, v * 2. - 1.),
+        }
+    }
+
+    // the ray of one eye through the point (u, v) of its half of the image, moved
+    // sideways from the one between the eyes and aimed at the same point at the
+    // convergence distance
+    pub fn eye_ray(&self, eye: Eye, u: f32, v: f32) -> Option<Ray> {
+        let stereo = self.stereo.unwrap_or_default();
+        let center = self.view_ray(u, v)?;
+        let forward = self.direction();
+        let up = Vector::new(0., 1., 0.);
+
+        let right = match self.projection {
+            // omni-directional stereo: the eyes turn with the ray, each column of
+            // the panorama has its own pair of eyes
+            Projection::Equirectangular => {
+                let horizontal = Vector::new(center.direction.x, 0., center.direction.z);
+                if horizontal.magnitude() > 1e-6 {
+                    horizontal.normalize().cross(&up).normalize()
+                } else {
+                    forward.cross(&up).normalize()
+                }
+            }
+            _ => forward.cross(&up).normalize(),
+        };
+        let side = match eye {
+            Eye::Left => -0.5,
+            Eye::Right => 0.5,
+        };
+        let origin = center.origin + right * (side * stereo.eye_separation);
+
+        // a plane in front of the flat projections, a sphere around the others
+        let distance = match self.projection {
+            Projection::Perspective | Projection::Orthographic => {
+                stereo.convergence / center.direction.dot(&forward)
+            }
+            _ => stereo.convergence,
+        };
+        let target = center.position(distance);
+
+        Some(Ray::new(origin, (target - origin).normalize()))
+    }
+
+    // ray through the point (u, v) seen from between the eyes, each eye has half
+    // of the image in stereo
+    pub fn view_ray(&self, u: f32, v: f32) -> Option<Ray> {
+        let aspect = match self.stereo.map(|stereo| stereo.layout) {
+            Some(StereoLayout::SideBySide) => self.aspect / 2.,
+            Some(StereoLayout::TopBottom) => self.aspect * 2.,
+            None => self.aspect,
+        };
+        let forward = self.direction();
+        let right = forward.cross(&Vector::new(0., 1., 0.)).normalize();
+        let up = right.cross(&forward);
+        let x = 2. * u - 1.;
+        let y = 1. - 2. * v;
+
+        match self.projection {
+            Projection::Perspective => {
+                let tan = (self.fov / 2.).tan();
+                let direction = forward + right * (x * tan * aspect) + up * (y * tan);
+
+                Some(Ray::new(self.position, direction.normalize()))
+            }
+            Projection::Orthographic => {
+                let half = self.view_height / 2.;
+                let origin = self.position + right * (x * half * aspect) + up * (y * half);
+
+                Some(Ray::new(origin, forward))
+            }
+            Projection::Fisheye => {
+                // the circle fits in the height of the image
+                let x = x * aspect;
+                let r = (x * x + y * y).sqrt();
+                if r > 1. {
+                    return None;
+                }
+
+                let theta = r * self.fov / 2.;
+                let phi = y.atan2(x);
+                let direction = forward * theta.cos()
+                    + (right * phi.cos() + up * phi.sin()) * theta.sin();
+
+                Some(Ray::new(self.position, direction.normalize()))
+            }
+            Projection::Equirectangular => {
+                // the middle of the image is in front, its sides behind the camera
+                let longitude = x * PI;
+                let latitude = y * PI / 2.;
+                let direction = forward * (latitude.cos() * longitude.cos())
+                    + right * (latitude.cos() * longitude.sin())
+                    + up * latitude.sin();
+
+                Some(Ray::new(self.position, direction.normalize()))
+            }
         }
     }
 
@@ -0,0 +1,100 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::structures::animation::{sort_keyframes, value_at};
+use crate::{Animatable, Camera, Keyframe, Point};
+
+// where the camera is and where it looks, angles in degrees like in Camera
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Point,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl CameraPose {
+    pub fn new(position: Point, pitch: f32, yaw: f32) -> Self {
+        Self {
+            position,
+            pitch,
+            yaw,
+        }
+    }
+}
+
+impl Animatable for CameraPose {
+    // the angles turn the short way, 350 to 10 goes through 0
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let turn = |from: f32, to: f32| {
+            let delta = (to - from + 540.).rem_euclid(360.) - 180.;
+            (from + delta * t).rem_euclid(360.)
+        };
+
+        Self {
+            position: self.position.lerp(other.position, t),
+            pitch: turn(self.pitch, other.pitch),
+            yaw: turn(self.yaw, other.yaw),
+        }
+    }
+}
+
+// the poses of a flight in the window, timed from the start of the recording
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CameraPath {
+    pub poses: Vec<Keyframe<CameraPose>>,
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.poses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.poses.is_empty()
+    }
+
+    // seconds from the first pose to the last
+    pub fn duration(&self) -> f32 {
+        match (self.poses.first(), self.poses.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.,
+        }
+    }
+
+    // adds the pose of the camera, the times have to keep growing
+    pub fn record(&mut self, camera: &Camera, time: f32) {
+        if self.poses.last().is_some_and(|last| last.time >= time) {
+            return;
+        }
+
+        self.poses.push(Keyframe::new(time, camera.pose()));
+    }
+
+    // pose at a time since the first one, between the recorded ones
+    pub fn pose_at(&self, time: f32) -> Option<CameraPose> {
+        let start = self.poses.first()?.time;
+
+        value_at(&self.poses, start + time)
+    }
+
+    pub fn save(&self, file_name: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        fs::write(file_name, json).map_err(|e| format!("{}: {}", file_name, e))
+    }
+
+    pub fn load(file_name: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+
+        let mut path: CameraPath =
+            serde_json::from_str(&json).map_err(|e| format!("{}: {}", file_name, e))?;
+        sort_keyframes(&mut path.poses);
+
+        Ok(path)
+    }
+}
@@ -1,7 +1,8 @@
 use std::fs::File;
 use std::io::{self, Write};
+use std::path::Path;
 
-use crate::Color;
+use crate::{Color, DisplaySettings};
 use crate::NewCanvas;
 
 #[derive(Debug)]
@@ -50,15 +51,11 @@ impl Canvas {
         }
     }
 
-    pub fn to_ppm(&self) -> String {
+    pub fn to_ppm(&self, display: &DisplaySettings) -> String {
         let mut ppm = format!("P3\n{} {}\n255\n", self.width, self.height);
         for y in 0..self.height {
             for x in 0..self.width {
-                let pixel = &self.at(x, y);
-
-                let r = (pixel.red().clamp(0.0, 1.0) * 255.0) as u32;
-                let g = (pixel.green().clamp(0.0, 1.0) * 255.0) as u32;
-                let b = (pixel.blue().clamp(0.0, 1.0) * 255.0) as u32;
+                let [r, g, b] = display.encode(self.at(x, y));
 
                 ppm.push_str(&format!("{} {} {} ", r, g, b));
             }
@@ -67,15 +64,48 @@ impl Canvas {
         ppm
     }
 
-    pub fn save_to_file(&self, filename: &str) -> io::Result<()> {
+    pub fn save_to_file(&self, filename: &str, display: &DisplaySettings) -> io::Result<()> {
         let mut file = File::create(filename)?;
-        let content = self.to_ppm();
+        let content = self.to_ppm(display);
         file.write_all(content.as_bytes())?;
 
         Ok(())
     }
 
-    pub fn pixels(&self) -> Vec<u32> {
-        self.pixels.iter().map(|c| c.clone().into()).collect()
+    // format from the extension: ppm, or anything the image crate writes (png, jpg, ...)
+    // exr keeps the linear values, without the display settings
+    pub fn save(&self, filename: &str, display: &DisplaySettings) -> Result<(), String> {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|ext| ext.to_ascii_lowercase());
+        if extension.as_ref().is_some_and(|ext| ext == "ppm") {
+            return self
+                .save_to_file(filename, display)
+                .map_err(|e| format!("{}: {}", filename, e));
+        }
+        if extension.as_ref().is_some_and(|ext| ext == "exr") {
+            let image = image::Rgb32FImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+                let pixel = self.at(x as usize, y as usize);
+
+                image::Rgb([pixel.red(), pixel.green(), pixel.blue()])
+            });
+
+            return image
+                .save(filename)
+                .map_err(|e| format!("{}: {}", filename, e));
+        }
+
+        let image = image::RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            image::Rgb(display.encode(self.at(x as usize, y as usize)))
+        });
+
+        image
+            .save(filename)
+            .map_err(|e| format!("{}: {}", filename, e))
+    }
+
+    // what the window shows
+    pub fn pixels(&self, display: &DisplaySettings) -> Vec<u32> {
+        self.pixels.iter().map(|c| display.pack(*c)).collect()
     }
 }
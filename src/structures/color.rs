@@ -11,6 +11,7 @@ pub struct Color {
 
 impl Color {
     pub const BLACK: Color = Color { r: 0.0, g: 0.0, b: 0.0 };
+    pub const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0 };
 
     pub fn new(r: f32, g: f32, b: f32) -> Self {
         Self { r, g, b }
@@ -36,6 +37,15 @@ impl Color {
         self.b
     }
 
+    pub fn is_black(&self) -> bool {
+        self.r <= 0.0 && self.g <= 0.0 && self.b <= 0.0
+    }
+
+    // perceived brightness (rec. 709 weights)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
 }
 
 impl Add for Color {
@@ -114,6 +124,6 @@ impl Into<u32> for Color {
         let g = (self.g.clamp(0.0, 1.0) * 255.0) as u32;
         let b = (self.b.clamp(0.0, 1.0) * 255.0) as u32;
 
-        (r << 16) | (g << 8) | (b << 0)
+        (r << 16) | (g << 8) | b
     }
 }
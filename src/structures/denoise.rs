@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Canvas, Color, NewCanvas};
+
+fn default_radius() -> usize {
+    3
+}
+
+fn default_color() -> f32 {
+    1.
+}
+
+fn default_albedo() -> f32 {
+    0.1
+}
+
+fn default_normal() -> f32 {
+    0.3
+}
+
+fn default_depth() -> f32 {
+    0.05
+}
+
+// joint bilateral filter: each pixel becomes an average of its neighbours,
+// weighted by how close they are and by how much they look like the same
+// surface in the albedo, normal and depth buffers, which have no noise
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    #[serde(default = "default_radius")]
+    pub radius: usize, // in pixels
+    #[serde(default = "default_color")]
+    pub color: f32, // the differences that still count, for the colors once tone mapped
+    #[serde(default = "default_albedo")]
+    pub albedo: f32,
+    #[serde(default = "default_normal")]
+    pub normal: f32,
+    #[serde(default = "default_depth")]
+    pub depth: f32, // relative to the distance of the pixel
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            radius: default_radius(),
+            color: default_color(),
+            albedo: default_albedo(),
+            normal: default_normal(),
+            depth: default_depth(),
+        }
+    }
+}
+
+impl Denoiser {
+    pub fn new(radius: usize) -> Self {
+        Self {
+            radius,
+            ..Self::default()
+        }
+    }
+
+    // the buffers have the size of the image, Renderer::update_image takes them
+    // from the first sample of each pixel
+    pub fn apply(
+        &self,
+        image: &Canvas,
+        albedo: &Canvas,
+        normal: &Canvas,
+        depth: &Canvas,
+    ) -> Canvas {
+        let (width, height) = (image.width, image.height);
+        let mut denoised = Canvas::new(NewCanvas { width, height });
+        let radius = self.radius as isize;
+        let spatial = 2. * (self.radius as f32 / 2.).max(0.5).powi(2);
+
+        for y in 0..height {
+            for x in 0..width {
+                let color = compress(image.at(x, y));
+                let (a, n, z) = (albedo.at(x, y), normal.at(x, y), depth.at(x, y).red());
+                let mut sum = Color::BLACK;
+                let mut total = 0.;
+
+                for dy in -radius..=radius {
+                    for dx in -radius..=radius {
+                        let (qx, qy) = (x as isize + dx, y as isize + dy);
+                        if qx < 0 || qy < 0 || qx >= width as isize || qy >= height as isize {
+                            continue;
+                        }
+                        let (qx, qy) = (qx as usize, qy as usize);
+
+                        let qz = depth.at(qx, qy).red();
+                        let depth_distance = match (z.is_finite(), qz.is_finite()) {
+                            (true, true) => (z - qz).abs() / (self.depth * z).max(1e-6),
+                            (false, false) => 0., // both on the background
+                            _ => continue,
+                        };
+
+                        let distance = (dx * dx + dy * dy) as f32 / spatial
+                            + distance(color, compress(image.at(qx, qy))) / sq(self.color)
+                            + distance(a, albedo.at(qx, qy)) / sq(self.albedo)
+                            + distance(n, normal.at(qx, qy)) / sq(self.normal)
+                            + depth_distance * depth_distance;
+                        let weight = (-0.5 * distance).exp();
+
+                        sum += image.at(qx, qy) * weight;
+                        total += weight;
+                    }
+                }
+
+                // the pixel itself always counts, total is never 0
+                denoised.write(x, y, sum * (1. / total));
+            }
+        }
+
+        denoised
+    }
+}
+
+// the highlights would decide alone otherwise
+fn compress(color: Color) -> Color {
+    let compress = |c: f32| {
+        let c = c.max(0.);
+        c / (1. + c)
+    };
+
+    Color::new(compress(color.r), compress(color.g), compress(color.b))
+}
+
+fn distance(a: Color, b: Color) -> f32 {
+    let d = a - b;
+
+    d.r * d.r + d.g * d.g + d.b * d.b
+}
+
+// of a tolerance, a tolerance of 0 keeps only the pixel itself
+fn sq(x: f32) -> f32 {
+    let x = x.max(1e-4);
+
+    x * x
+}
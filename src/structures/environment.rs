@@ -0,0 +1,355 @@
+use std::f32::consts::PI;
+use std::path::Path;
+
+use crate::structures::sampler::Distribution;
+use crate::structures::tonemap::srgb_to_linear;
+use crate::{Color, Vector};
+
+use image::ColorType;
+use serde::{Deserialize, Serialize};
+
+// light added to every hit, whatever the lights are
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Ambient {
+    pub color: Color,
+    pub intensity: f32,
+}
+
+impl Default for Ambient {
+    fn default() -> Self {
+        Self {
+            color: Color::new(1., 1., 1.),
+            intensity: 0.1,
+        }
+    }
+}
+
+impl Ambient {
+    pub fn new(color: Color, intensity: f32) -> Self {
+        Self { color, intensity }
+    }
+
+    pub fn light(&self) -> Color {
+        self.color * self.intensity
+    }
+}
+
+// what the rays that miss every object see
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Background {
+    Solid(Color),
+    Gradient {
+        top: Color,
+        bottom: Color,
+    },
+    SunSky {
+        sun_direction: Vector, // toward the sun
+        #[serde(default = "default_sun_color")]
+        sun_color: Color,
+        #[serde(default = "default_sun_size")]
+        sun_size: f32, // angular radius in degrees
+        #[serde(default = "default_zenith")]
+        zenith: Color,
+        #[serde(default = "default_horizon")]
+        horizon: Color,
+        #[serde(default = "default_ground")]
+        ground: Color,
+    },
+    Environment(EnvironmentMap),
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Solid(Color::BLACK)
+    }
+}
+
+fn default_sun_color() -> Color {
+    Color::new(10., 9., 7.5)
+}
+
+fn default_sun_size() -> f32 {
+    0.5
+}
+
+fn default_zenith() -> Color {
+    Color::new(0.2, 0.4, 0.85)
+}
+
+fn default_horizon() -> Color {
+    Color::new(0.8, 0.85, 0.95)
+}
+
+fn default_ground() -> Color {
+    Color::new(0.3, 0.28, 0.25)
+}
+
+impl Background {
+    pub fn sun_sky(sun_direction: Vector) -> Self {
+        Background::SunSky {
+            sun_direction: sun_direction.normalize(),
+            sun_color: default_sun_color(),
+            sun_size: default_sun_size(),
+            zenith: default_zenith(),
+            horizon: default_horizon(),
+            ground: default_ground(),
+        }
+    }
+
+    pub fn color_at(&self, direction: &Vector) -> Color {
+        let direction = direction.normalize();
+
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { top, bottom } => {
+                let t = 0.5 * (direction.y + 1.);
+                *bottom + (*top - *bottom) * t
+            }
+            Background::SunSky {
+                sun_direction,
+                sun_color,
+                sun_size,
+                zenith,
+                horizon,
+                ground,
+            } => {
+                if direction.y < 0. {
+                    // quick fade so the horizon is not a hard line
+                    let t = (-direction.y * 10.).min(1.);
+                    return *horizon + (*ground - *horizon) * t;
+                }
+
+                let sky = *horizon + (*zenith - *horizon) * direction.y.sqrt();
+
+                let cos_sun = direction.dot(&sun_direction.normalize());
+                if cos_sun >= sun_size.to_radians().cos() {
+                    return *sun_color;
+                }
+
+                let glow = cos_sun.max(0.).powi(64) * 0.05;
+                sky + *sun_color * glow
+            }
+            Background::Environment(map) => map.radiance(&direction),
+        }
+    }
+
+    // reads the files the background needs, relative paths start from `base`
+    pub fn load(&mut self, base: Option<&Path>) -> Result<(), String> {
+        match self {
+            Background::Environment(map) => map.load(base),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn default_intensity() -> f32 {
+    1.
+}
+
+fn default_lighting() -> bool {
+    true
+}
+
+fn default_environment_samples() -> usize {
+    16
+}
+
+// latitude / longitude picture of everything around the scene, hdr or not
+// the pixels are read by `load`, only the settings are in the scene file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnvironmentMap {
+    pub path: String,
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+    #[serde(default)]
+    pub rotation: f32, // degrees around the y axis
+    #[serde(default = "default_lighting")]
+    pub lighting: bool, // false: only seen in the background, lights nothing
+    #[serde(default = "default_environment_samples")]
+    pub samples: usize, // per strategy and per shaded point
+
+    #[serde(skip)]
+    width: usize,
+    #[serde(skip)]
+    height: usize,
+    #[serde(skip)]
+    pixels: Vec<Color>,
+    #[serde(skip)]
+    rows: Distribution,
+    #[serde(skip)]
+    columns: Vec<Distribution>,
+}
+
+impl EnvironmentMap {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            intensity: default_intensity(),
+            rotation: 0.,
+            lighting: default_lighting(),
+            samples: default_environment_samples(),
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+            rows: Distribution::default(),
+            columns: Vec::new(),
+        }
+    }
+
+    // map made in memory, row by row from the top (+y)
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        let mut map = EnvironmentMap::new("");
+        map.set_pixels(width, height, pixels);
+
+        map
+    }
+
+    pub fn intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+
+        self
+    }
+
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+
+        self
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.pixels.is_empty()
+    }
+
+    pub fn load(&mut self, base: Option<&Path>) -> Result<(), String> {
+        let path = match base {
+            Some(base) if Path::new(&self.path).is_relative() => base.join(&self.path),
+            _ => Path::new(&self.path).to_path_buf(),
+        };
+
+        let picture = image::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // 8 and 16 bits pictures are stored in srgb, hdr ones are already linear
+        let linear = matches!(picture.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+        let picture = picture.to_rgb32f();
+
+        let pixels = picture
+            .pixels()
+            .map(|p| {
+                if linear {
+                    Color::new(p[0], p[1], p[2])
+                } else {
+                    Color::new(srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2]))
+                }
+            })
+            .collect();
+
+        self.set_pixels(picture.width() as usize, picture.height() as usize, pixels);
+
+        Ok(())
+    }
+
+    // the brightest parts are sampled the most, sin(theta) because the rows
+    // near the poles cover less of the sphere
+    fn set_pixels(&mut self, width: usize, height: usize, pixels: Vec<Color>) {
+        let mut columns = Vec::with_capacity(height);
+        let mut row_weights = Vec::with_capacity(height);
+
+        for row in 0..height {
+            let sin_theta = (PI * (row as f32 + 0.5) / height as f32).sin();
+            let weights: Vec<f32> = pixels[row * width..(row + 1) * width]
+                .iter()
+                .map(|c| c.luminance() * sin_theta)
+                .collect();
+
+            let distribution = Distribution::new(&weights);
+            row_weights.push(distribution.total());
+            columns.push(distribution);
+        }
+
+        self.width = width;
+        self.height = height;
+        self.pixels = pixels;
+        self.rows = Distribution::new(&row_weights);
+        self.columns = columns;
+    }
+
+    fn direction_to_uv(&self, direction: &Vector) -> (f32, f32) {
+        let d = direction.normalize();
+        let u = d.x.atan2(-d.z) / (2. * PI) + 0.5 + self.rotation / 360.;
+        let v = d.y.clamp(-1., 1.).acos() / PI;
+
+        (u.rem_euclid(1.), v)
+    }
+
+    fn uv_to_direction(&self, u: f32, v: f32) -> Vector {
+        let phi = (u - 0.5 - self.rotation / 360.) * 2. * PI;
+        let theta = v * PI;
+
+        Vector::new(theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos())
+    }
+
+    fn pixel_index(&self, u: f32, v: f32) -> (usize, usize) {
+        let column = ((u * self.width as f32) as usize).min(self.width - 1);
+        let row = ((v * self.height as f32) as usize).min(self.height - 1);
+
+        (row, column)
+    }
+
+    pub fn radiance(&self, direction: &Vector) -> Color {
+        if !self.is_loaded() {
+            return Color::BLACK;
+        }
+
+        let (u, v) = self.direction_to_uv(direction);
+        let (row, column) = self.pixel_index(u, v);
+
+        self.pixels[row * self.width + column] * self.intensity
+    }
+
+    // chance per unit of solid angle of `sample` returning this direction
+    pub fn pdf(&self, direction: &Vector) -> f32 {
+        if !self.is_loaded() || self.rows.is_empty() {
+            return 0.;
+        }
+
+        let (u, v) = self.direction_to_uv(direction);
+        let (row, column) = self.pixel_index(u, v);
+
+        self.solid_angle_pdf(row, column, v)
+    }
+
+    fn solid_angle_pdf(&self, row: usize, column: usize, v: f32) -> f32 {
+        let sin_theta = (v * PI).sin();
+        if sin_theta <= 0. {
+            return 0.;
+        }
+
+        let p = self.rows.probability(row) * self.columns[row].probability(column);
+        p * (self.width * self.height) as f32 / (2. * PI * PI * sin_theta)
+    }
+
+    // direction toward the map chosen by its brightness, with its light and pdf
+    pub fn sample(&self, s: f32, t: f32) -> Option<(Vector, Color, f32)> {
+        if !self.is_loaded() || self.rows.is_empty() {
+            return None;
+        }
+
+        let (row, dv) = self.rows.sample(t);
+        let (column, du) = self.columns[row].sample(s);
+        let u = (column as f32 + du) / self.width as f32;
+        let v = (row as f32 + dv) / self.height as f32;
+
+        let pdf = self.solid_angle_pdf(row, column, v);
+        if pdf <= 0. {
+            return None;
+        }
+
+        let radiance = self.pixels[row * self.width + column] * self.intensity;
+        Some((self.uv_to_direction(u, v), radiance, pdf))
+    }
+}
@@ -0,0 +1,196 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Color;
+
+// how much a sample counts for a pixel depending on their distance
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Filter {
+    #[default]
+    Box,      // flat average of the samples inside the pixel
+    Tent,     // linear falloff
+    Gaussian, // smooth, a bit blurry
+    Mitchell, // sharper, negative lobes
+}
+
+impl Filter {
+    // distance in pixels after which a sample doesn't count anymore
+    pub fn default_radius(&self) -> f32 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.,
+        }
+    }
+
+    pub fn weight(&self, dx: f32, dy: f32, radius: f32) -> f32 {
+        self.weight_1d(dx, radius) * self.weight_1d(dy, radius)
+    }
+
+    fn weight_1d(&self, d: f32, radius: f32) -> f32 {
+        let d = d.abs();
+        if d >= radius {
+            return 0.;
+        }
+
+        match self {
+            Filter::Box => 1.,
+            Filter::Tent => 1. - d / radius,
+            Filter::Gaussian => {
+                // shifted so it reaches 0 at the radius
+                let alpha = 2.;
+                ((-alpha * d * d).exp() - (-alpha * radius * radius).exp()).max(0.)
+            }
+            Filter::Mitchell => mitchell(2. * d / radius),
+        }
+    }
+}
+
+// B = C = 1/3, x between 0 and 2
+fn mitchell(x: f32) -> f32 {
+    let (b, c) = (1. / 3., 1. / 3.);
+
+    if x < 1. {
+        ((12. - 9. * b - 6. * c) * x * x * x + (-18. + 12. * b + 6. * c) * x * x + (6. - 2. * b))
+            / 6.
+    } else if x < 2. {
+        ((-b - 6. * c) * x * x * x
+            + (6. * b + 30. * c) * x * x
+            + (-12. * b - 48. * c) * x
+            + (8. * b + 24. * c))
+            / 6.
+    } else {
+        0.
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "box" => Ok(Filter::Box),
+            "tent" => Ok(Filter::Tent),
+            "gaussian" => Ok(Filter::Gaussian),
+            "mitchell" => Ok(Filter::Mitchell),
+            _ => Err(format!(
+                "unknown filter '{}', expected box, tent, gaussian or mitchell",
+                s
+            )),
+        }
+    }
+}
+
+// accumulates the samples of a frame, each one shared between the pixels
+// its filter reaches
+#[derive(Debug, Clone)]
+pub struct Film {
+    pub width: usize,
+    pub height: usize,
+    filter: Filter,
+    radius: f32,
+    sums: Vec<Color>,
+    weights: Vec<f32>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Filter, radius: f32) -> Self {
+        Self {
+            width,
+            height,
+            filter,
+            radius: radius.max(0.5),
+            sums: vec![Color::BLACK; width * height],
+            weights: vec![0.; width * height],
+        }
+    }
+
+    // x and y in pixels, the center of the pixel (i, j) is at (i + 0.5, j + 0.5)
+    pub fn add_sample(&mut self, x: f32, y: f32, color: Color) {
+        let x0 = (x - 0.5 - self.radius).ceil().max(0.) as usize;
+        let y0 = (y - 0.5 - self.radius).ceil().max(0.) as usize;
+        let x1 = ((x - 0.5 + self.radius).floor() as isize).min(self.width as isize - 1);
+        let y1 = ((y - 0.5 + self.radius).floor() as isize).min(self.height as isize - 1);
+
+        if x1 < 0 || y1 < 0 {
+            return;
+        }
+
+        for j in y0..=y1 as usize {
+            for i in x0..=x1 as usize {
+                let weight = self.filter.weight(
+                    i as f32 + 0.5 - x,
+                    j as f32 + 0.5 - y,
+                    self.radius,
+                );
+                if weight != 0. {
+                    let index = j * self.width + i;
+                    self.sums[index] += color * weight;
+                    self.weights[index] += weight;
+                }
+            }
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let index = y * self.width + x;
+        let weight = self.weights[index];
+
+        // the negative lobes of mitchell can cancel everything out
+        if weight.abs() < 1e-6 {
+            return Color::BLACK;
+        }
+
+        let color = self.sums[index] * (1. / weight);
+        Color::new(color.red().max(0.), color.green().max(0.), color.blue().max(0.))
+    }
+}
+
+// largest difference between two colors once brought back to what the screen shows
+pub fn contrast(a: &Color, b: &Color) -> f32 {
+    let channel = |x: f32, y: f32| (x.clamp(0., 1.) - y.clamp(0., 1.)).abs();
+
+    channel(a.red(), b.red())
+        .max(channel(a.green(), b.green()))
+        .max(channel(a.blue(), b.blue()))
+}
+
+// pixels that differ from one of their neighbours, by their color or by the
+// object seen through them (None for the background)
+pub fn contrast_mask(
+    width: usize,
+    height: usize,
+    colors: &[Color],
+    ids: &[Option<usize>],
+    threshold: f32,
+) -> Vec<bool> {
+    let mut mask = vec![false; width * height];
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let mut neighbours = Vec::with_capacity(4);
+
+            if x > 0 {
+                neighbours.push(index - 1);
+            }
+            if x + 1 < width {
+                neighbours.push(index + 1);
+            }
+            if y > 0 {
+                neighbours.push(index - width);
+            }
+            if y + 1 < height {
+                neighbours.push(index + width);
+            }
+
+            mask[index] = neighbours.iter().any(|&other| {
+                ids[other] != ids[index] || contrast(&colors[other], &colors[index]) > threshold
+            });
+        }
+    }
+
+    mask
+}
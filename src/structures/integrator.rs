@@ -0,0 +1,380 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::structures::brdf::Bsdf;
+use crate::structures::sampler::{Rng, power_heuristic, uniform_cone};
+use crate::{
+    Background, Color, EPSILON, Intersection, LightKind, LightModel, Object, Point, Ray, Vector,
+    World,
+};
+
+// paths shorter than this are never stopped by the russian roulette
+const MIN_DEPTH: usize = 3;
+
+// how the color of a camera ray is computed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Integrator {
+    #[default]
+    Whitted, // phong, hard reflections, fast
+    PathTracer, // global illumination, needs many samples per pixel
+}
+
+impl FromStr for Integrator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "whitted" => Ok(Integrator::Whitted),
+            "path" | "pathtracer" => Ok(Integrator::PathTracer),
+            _ => Err(format!(
+                "unknown integrator '{}', expected whitted or path",
+                s
+            )),
+        }
+    }
+}
+
+// unbiased monte carlo path tracing: at each bounce the lights are sampled
+// directly, then the material picks the next direction
+// the constant ambient of the world is not used, the indirect light replaces it
+#[derive(Debug, Clone)]
+pub struct PathTracer {
+    pub max_depth: usize,
+}
+
+impl PathTracer {
+    pub fn new(max_depth: usize) -> Self {
+        Self { max_depth }
+    }
+
+    pub fn radiance(&self, world: &World, ray: &Ray, rng: &mut Rng) -> Color {
+        self.trace(world, ray, rng).0
+    }
+
+    // light coming back along the ray, and the first object it hit
+    pub fn trace(&self, world: &World, ray: &Ray, rng: &mut Rng) -> (Color, Option<usize>) {
+        let (direct, reflected, first_hit) = self.trace_split(world, ray, rng);
+
+        (direct + reflected, first_hit)
+    }
+
+    // the same light in two parts: what reaches the first hit straight from the
+    // lights, the emitters and the sky (or the camera when nothing is hit), and
+    // what bounced before
+    pub fn trace_split(
+        &self,
+        world: &World,
+        ray: &Ray,
+        rng: &mut Rng,
+    ) -> (Color, Color, Option<usize>) {
+        let hit = world.intersect(ray, 1.);
+        let first_hit = hit.as_ref().map(|hit| hit.object_id());
+        let (direct, reflected) = self.trace_from(world, ray, hit, rng);
+
+        (direct, reflected, first_hit)
+    }
+
+    // the same from the first hit of the ray, already found by the caller
+    pub fn trace_from(
+        &self,
+        world: &World,
+        ray: &Ray,
+        first_hit: Option<Intersection>,
+        rng: &mut Rng,
+    ) -> (Color, Color) {
+        let mut light = [Color::BLACK; 2];
+        let mut throughput = Color::WHITE;
+        let mut ray = Ray::new(ray.origin, ray.direction.normalize()).at_time(ray.time);
+        let time = ray.time;
+        let mut first_hit = Some(first_hit);
+        // pdf of the material bounce that made the ray, 0 for camera and mirror rays
+        let mut bsdf_pdf = 0.;
+
+        for depth in 0..self.max_depth.max(1) {
+            // the material bounce from the first hit still finds the lights directly
+            let part = if depth == 0 || (depth == 1 && bsdf_pdf > 0.) {
+                0
+            } else {
+                1
+            };
+
+            let hit = match first_hit.take().unwrap_or_else(|| world.intersect(&ray, 1.)) {
+                Some(hit) => hit,
+                None => {
+                    light[part] += throughput * escaped(world, &ray.direction, bsdf_pdf);
+                    break;
+                }
+            };
+
+            let obj = hit.object;
+            let material = &obj.material;
+            let point = hit.point;
+            let wo = -ray.direction;
+            let outside = hit.normal.dot(&wo) >= 0.;
+            let normal = if outside { hit.normal } else { -hit.normal };
+
+            let emitted = material.emitted();
+            if !emitted.is_black() {
+                // a material bounce could also have found it by sampling the emitters
+                let weight = if bsdf_pdf > 0. {
+                    power_heuristic(bsdf_pdf, emitter_pdf(obj, &ray.origin, &ray.direction))
+                } else {
+                    1.
+                };
+                light[part] += throughput * emitted * weight;
+            }
+
+            // one of the three parts of the material, by their weight
+            let reflective = material.reflective.clamp(0., 1.);
+            let transparency = material.transparency.clamp(0., 1. - reflective);
+            let choice = rng.next_f32();
+
+            if choice < reflective {
+                ray = Ray::new(point + normal * EPSILON, ray.direction.reflect(&normal))
+                    .at_time(time);
+                bsdf_pdf = 0.;
+            } else if choice < reflective + transparency {
+                let (n1, n2) = if outside {
+                    (1., material.refractive_index)
+                } else {
+                    (material.refractive_index, 1.)
+                };
+                let cos_i = wo.dot(&normal);
+                let refracted = refract(&ray.direction, &normal, n1 / n2);
+                let reflectance = match refracted {
+                    Some(_) => schlick(cos_i, n1, n2),
+                    None => 1.,
+                };
+
+                ray = match refracted {
+                    Some(direction) if rng.next_f32() >= reflectance => {
+                        // the glass tints once, on the way in
+                        if outside {
+                            throughput *= obj.color_at(&point);
+                        }
+                        Ray::new(point - normal * EPSILON, direction).at_time(time)
+                    }
+                    _ => Ray::new(point + normal * EPSILON, ray.direction.reflect(&normal))
+                        .at_time(time),
+                };
+                bsdf_pdf = 0.;
+            } else {
+                let brdf = Bsdf::from_object(obj, &point);
+                let origin = point + normal * EPSILON;
+
+                light[depth.min(1)] +=
+                    throughput * direct_lighting(world, &brdf, &origin, &wo, &normal, time, rng);
+
+                let (u, v, w) = (rng.next_f32(), rng.next_f32(), rng.next_f32());
+                let wi = match brdf.sample(&wo, &normal, u, v, w) {
+                    Some(wi) => wi,
+                    None => break,
+                };
+                let pdf = brdf.pdf(&wi, &wo, &normal);
+                if pdf <= 0. {
+                    break;
+                }
+
+                throughput *= brdf.eval(&wi, &wo, &normal) * (wi.dot(&normal) / pdf);
+                ray = Ray::new(origin, wi).at_time(time);
+                bsdf_pdf = pdf;
+            }
+
+            // russian roulette: the paths carrying little light stop early,
+            // the ones that survive count for those that didn't
+            if depth + 1 >= MIN_DEPTH {
+                let survival = throughput
+                    .red()
+                    .max(throughput.green())
+                    .max(throughput.blue())
+                    .min(0.95);
+
+                if rng.next_f32() >= survival {
+                    break;
+                }
+                throughput *= 1. / survival;
+            }
+        }
+
+        (light[0], light[1])
+    }
+}
+
+// what a ray leaving the scene brings back
+fn escaped(world: &World, direction: &Vector, bsdf_pdf: f32) -> Color {
+    let background = world.background.color_at(direction);
+
+    match &world.background {
+        // already sampled by the direct lighting, the weights of both add up to 1
+        Background::Environment(map) if bsdf_pdf > 0. => {
+            if !map.lighting {
+                return Color::BLACK;
+            }
+            background * power_heuristic(bsdf_pdf, map.pdf(direction))
+        }
+        _ => background,
+    }
+}
+
+// one shadow ray toward each light, each glowing object and the environment map
+fn direct_lighting(
+    world: &World,
+    brdf: &Bsdf,
+    point: &Point,
+    wo: &Vector,
+    normal: &Vector,
+    time: f32,
+    rng: &mut Rng,
+) -> Color {
+    let mut color = Color::BLACK;
+    let light_number = world.lights.len();
+
+    for light in &world.lights {
+        let falloff = light.falloff(point);
+        if falloff <= 0. {
+            continue;
+        }
+
+        let (wi, distance, attenuation) = match light.kind {
+            LightKind::Directional { .. } => (light.direction_from(point), f32::INFINITY, 1.),
+            _ => {
+                let v = light.sample_position(point, rng) - *point;
+                let distance = v.magnitude();
+
+                (
+                    v * (1. / distance),
+                    distance,
+                    light.attenuation.factor(distance),
+                )
+            }
+        };
+
+        let f = brdf.eval(&wi, wo, normal);
+        if f.is_black() {
+            continue;
+        }
+
+        let visibility = world.transmittance_at(point, &wi, distance, time);
+        if visibility.is_black() {
+            continue;
+        }
+
+        let scale = match world.light_model {
+            LightModel::Physical => attenuation,
+            LightModel::Legacy => 1. / light_number as f32,
+        };
+
+        // the intensities are tuned for the phong shading, times pi a white
+        // diffuse surface is as bright with both integrators
+        color += light.intensity * f * visibility * (wi.dot(normal) * falloff * scale * PI);
+    }
+
+    color += emitter_lighting(world, brdf, point, wo, normal, time, rng);
+
+    if let Background::Environment(map) = &world.background
+        && map.lighting
+        && let Some((wi, radiance, pdf)) = map.sample(rng.next_f32(), rng.next_f32())
+    {
+        let f = brdf.eval(&wi, wo, normal);
+
+        if !f.is_black() {
+            let weight = power_heuristic(pdf, brdf.pdf(&wi, wo, normal));
+            let visibility = world.transmittance_at(point, &wi, f32::INFINITY, time);
+            color += radiance * f * visibility * (wi.dot(normal) * weight / pdf);
+        }
+    }
+
+    color
+}
+
+// cone from the point around the bounding sphere of the emitter, None when the
+// point is inside it or the object has no end (planes are only found by bounces)
+fn emitter_cone(emitter: &Object, point: &Point) -> Option<(Vector, f32)> {
+    let (center, radius) = emitter.bounding_sphere()?;
+    let to_center = center - *point;
+    let distance = to_center.magnitude();
+    if distance <= radius {
+        return None;
+    }
+
+    let sin_max = radius / distance;
+    let cos_max = (1. - sin_max * sin_max).sqrt();
+    // too small to be seen from here
+    if cos_max >= 1. {
+        return None;
+    }
+
+    Some((to_center * (1. / distance), cos_max))
+}
+
+// chance of emitter_lighting choosing this direction from the point
+fn emitter_pdf(emitter: &Object, point: &Point, direction: &Vector) -> f32 {
+    match emitter_cone(emitter, point) {
+        Some((axis, cos_max)) if axis.dot(&direction.normalize()) >= cos_max => {
+            1. / (2. * PI * (1. - cos_max))
+        }
+        _ => 0.,
+    }
+}
+
+// a ray toward each emitter, it counts if nothing is in the way
+fn emitter_lighting(
+    world: &World,
+    brdf: &Bsdf,
+    point: &Point,
+    wo: &Vector,
+    normal: &Vector,
+    time: f32,
+    rng: &mut Rng,
+) -> Color {
+    let mut color = Color::BLACK;
+
+    for emitter in world
+        .objects
+        .iter()
+        .filter(|o| o.material.emission.is_some())
+    {
+        let (axis, cos_max) = match emitter_cone(emitter, point) {
+            Some(cone) => cone,
+            None => continue,
+        };
+
+        let wi = uniform_cone(&axis, cos_max, rng.next_f32(), rng.next_f32());
+        let f = brdf.eval(&wi, wo, normal);
+        if f.is_black() {
+            continue;
+        }
+
+        if let Some(hit) = world.intersect(&Ray::new(*point, wi).at_time(time), 1.)
+            && std::ptr::eq(hit.object, emitter)
+        {
+            let pdf = 1. / (2. * PI * (1. - cos_max));
+            let weight = power_heuristic(pdf, brdf.pdf(&wi, wo, normal));
+
+            color += emitter.material.emitted() * f * (wi.dot(normal) * weight / pdf);
+        }
+    }
+
+    color
+}
+
+// direction through the surface, None for a total internal reflection
+fn refract(direction: &Vector, normal: &Vector, eta: f32) -> Option<Vector> {
+    let cos_i = -direction.dot(normal);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i);
+    if sin2_t > 1. {
+        return None;
+    }
+
+    let cos_t = (1. - sin2_t).sqrt();
+    Some((*direction * eta + *normal * (eta * cos_i - cos_t)).normalize())
+}
+
+// part of the light reflected by a glass surface
+fn schlick(cos_i: f32, n1: f32, n2: f32) -> f32 {
+    let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+
+    r0 + (1. - r0) * (1. - cos_i).powi(5)
+}
@@ -11,6 +11,7 @@ pub struct Intersection<'a> {
     pub over_point: Point,
     pub reflectv: Vector,
     pub n1: f32, // ca c'est le milieu de depart, c'est de la merde, je veux pas le mettre la
+    pub time: f32, // of the ray, the rays sent from the hit happen at the same moment
 }
 
 impl <'a> Intersection <'a> {
@@ -34,8 +35,14 @@ impl <'a> Intersection <'a> {
             over_point,
             reflectv,
             n1,
+            time: 0.,
         }
     }
+
+    // tells the hit objects apart, stable as long as the world doesn't change
+    pub fn object_id(&self) -> usize {
+        self.object as *const Object as usize
+    }
 }
 
 // trait Intersect have to be implemented in Intersect
@@ -1,12 +1,97 @@
+use std::f32::consts::PI;
 
-use crate::{Color, Point};
+use crate::structures::animation::value_at;
+use crate::structures::sampler::{Rng, basis, stratified};
+use crate::{Color, EPSILON, LightAnimation, Point, Vector};
 
 use serde::{Deserialize, Serialize};
 
+// emitting surface of an area light, centered on the light position
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AreaShape {
+    Rectangle { u: Vector, v: Vector }, // the two edges
+    Disk { normal: Vector, radius: f32 },
+    Sphere { radius: f32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum LightKind {
+    #[default]
+    Point,
+    Directional {
+        direction: Vector, // where the light goes, like the sun rays
+    },
+    Spot {
+        direction: Vector,
+        inner_angle: f32, // degrees from the axis, full intensity inside
+        outer_angle: f32, // degrees from the axis, no light outside
+    },
+    Area {
+        shape: AreaShape,
+        #[serde(default = "default_samples")]
+        samples: u32, // shadow rays per shaded point, rounded up to a square
+    },
+}
+
+fn default_samples() -> u32 {
+    16
+}
+
+// intensity is divided by constant + linear * d + quadratic * d^2
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for Attenuation {
+    // inverse square law
+    fn default() -> Self {
+        Self {
+            constant: 0.,
+            linear: 0.,
+            quadratic: 1.,
+        }
+    }
+}
+
+impl Attenuation {
+    pub fn new(constant: f32, linear: f32, quadratic: f32) -> Self {
+        Self {
+            constant,
+            linear,
+            quadratic,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::new(1., 0., 0.)
+    }
+
+    pub fn factor(&self, distance: f32) -> f32 {
+        let denominator = self.constant + self.linear * distance + self.quadratic * distance * distance;
+
+        // avoid the infinite intensity when the point touches the light
+        1. / denominator.max(EPSILON)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Light {
-    pub position: Point,
+    #[serde(default = "default_position")]
+    pub position: Point, // unused by directional lights
     pub intensity: Color,
+    #[serde(default)]
+    pub kind: LightKind,
+    #[serde(default)]
+    pub attenuation: Attenuation, // ignored by directional lights and the legacy light model
+    #[serde(default)]
+    pub animation: Option<LightAnimation>,
+}
+
+fn default_position() -> Point {
+    Point::new(0., 0., 0.)
 }
 
 impl Light {
@@ -14,6 +99,175 @@ impl Light {
         Self {
             position,
             intensity,
+            kind: LightKind::Point,
+            attenuation: Attenuation::default(),
+            animation: None,
+        }
+    }
+
+    pub fn directional(direction: Vector, intensity: Color) -> Self {
+        Self {
+            position: default_position(),
+            intensity,
+            kind: LightKind::Directional {
+                direction: direction.normalize(),
+            },
+            attenuation: Attenuation::none(),
+            animation: None,
+        }
+    }
+
+    pub fn spot(
+        position: Point,
+        direction: Vector,
+        inner_angle: f32,
+        outer_angle: f32,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            position,
+            intensity,
+            kind: LightKind::Spot {
+                direction: direction.normalize(),
+                inner_angle,
+                outer_angle,
+            },
+            attenuation: Attenuation::default(),
+            animation: None,
+        }
+    }
+
+    pub fn area(position: Point, shape: AreaShape, samples: u32, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            kind: LightKind::Area { shape, samples },
+            attenuation: Attenuation::default(),
+            animation: None,
+        }
+    }
+
+    pub fn attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+
+        self
+    }
+
+    pub fn animation(mut self, animation: LightAnimation) -> Self {
+        self.animation = Some(animation);
+
+        self
+    }
+
+    // position and intensity from the keyframes at a time in seconds
+    pub fn animate(&mut self, time: f32) {
+        if let Some(animation) = &self.animation {
+            self.position = value_at(&animation.position, time).unwrap_or(self.position);
+            self.intensity = value_at(&animation.intensity, time).unwrap_or(self.intensity);
+        }
+    }
+
+    // part of the intensity left after travelling to the point
+    pub fn attenuation_at(&self, point: &Point) -> f32 {
+        match &self.kind {
+            LightKind::Directional { .. } => 1.,
+            _ => self.attenuation.factor(self.distance_from(point)),
+        }
+    }
+
+    // normalized vector from the point toward the light
+    pub fn direction_from(&self, point: &Point) -> Vector {
+        match &self.kind {
+            LightKind::Point | LightKind::Spot { .. } | LightKind::Area { .. } => {
+                (self.position - *point).normalize()
+            }
+            LightKind::Directional { direction } => -direction.normalize(),
+        }
+    }
+
+    // how far a shadow ray has to go, a directional light is infinitely far
+    pub fn distance_from(&self, point: &Point) -> f32 {
+        match &self.kind {
+            LightKind::Point | LightKind::Spot { .. } | LightKind::Area { .. } => {
+                (self.position - *point).magnitude()
+            }
+            LightKind::Directional { .. } => f32::INFINITY,
+        }
+    }
+
+    // part of the intensity reaching the point, smooth between the two cones of a spot
+    pub fn falloff(&self, point: &Point) -> f32 {
+        match &self.kind {
+            LightKind::Spot {
+                direction,
+                inner_angle,
+                outer_angle,
+            } => {
+                let cos_angle = (*point - self.position).normalize().dot(&direction.normalize());
+                let cos_inner = inner_angle.to_radians().cos();
+                let cos_outer = outer_angle.max(*inner_angle).to_radians().cos();
+
+                if cos_angle >= cos_inner {
+                    1.
+                } else if cos_angle <= cos_outer {
+                    0.
+                } else {
+                    let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+                    t * t * (3. - 2. * t)
+                }
+            }
+            _ => 1.,
+        }
+    }
+
+    // stratified points on the surface of an area light, seen from `point`
+    pub fn sample_positions(&self, point: &Point, rng: &mut Rng) -> Vec<Point> {
+        let (shape, samples) = match &self.kind {
+            LightKind::Area { shape, samples } => (shape, *samples),
+            _ => return vec![self.position],
+        };
+
+        let n = (samples.max(1) as f32).sqrt().ceil() as usize;
+
+        stratified(n, rng)
+            .into_iter()
+            .map(|(s, t)| self.position_on(shape, s, t, point))
+            .collect()
+    }
+
+    // a single random point of the light, for the integrators that trace many paths
+    pub fn sample_position(&self, point: &Point, rng: &mut Rng) -> Point {
+        match &self.kind {
+            LightKind::Area { shape, .. } => {
+                self.position_on(shape, rng.next_f32(), rng.next_f32(), point)
+            }
+            _ => self.position,
+        }
+    }
+
+    fn position_on(&self, shape: &AreaShape, s: f32, t: f32, point: &Point) -> Point {
+        match shape {
+            AreaShape::Rectangle { u, v } => self.position + *u * (s - 0.5) + *v * (t - 0.5),
+            AreaShape::Disk { normal, radius } => {
+                let (tangent, bitangent) = basis(&normal.normalize());
+                let r = radius * s.sqrt();
+                let phi = 2. * PI * t;
+
+                self.position + tangent * (r * phi.cos()) + bitangent * (r * phi.sin())
+            }
+            AreaShape::Sphere { radius } => {
+                let z = 1. - 2. * s;
+                let r = (1. - z * z).max(0.).sqrt();
+                let phi = 2. * PI * t;
+                let mut direction = Vector::new(r * phi.cos(), r * phi.sin(), z);
+
+                // only the half facing the point can be seen from it
+                if direction.dot(&(*point - self.position)) < 0. {
+                    direction = -direction;
+                }
+
+                self.position + direction * *radius
+            }
         }
     }
 }
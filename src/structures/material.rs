@@ -1,19 +1,114 @@
 use crate::{Color, Pattern};
 use std::default::Default;
 
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone, )]
 pub struct Material {
     pub color: Color,
     pub shininess: f32,  // between 10 and 200
-    pub ambient: f32,    // between 0 and 1
+    pub ambient: f32,    // between 0 and 1, legacy light model only
     pub diffuse: f32,    // between 0 and 1
     pub specular: f32,   // between 0 and 1
     pub reflective: f32, // between 0 and 1
     pub refractive_index: f32,
     pub pattern: Option<Pattern>,
     pub transparency: f32,
+    #[serde(default)]
+    pub pbr: Option<Pbr>, // replaces shininess, diffuse and specular when set
+    #[serde(default)]
+    pub emission: Option<Emission>,
+}
+
+fn default_strength() -> f32 {
+    1.
+}
+
+// the same check as Emission::new, a surface can't take light away
+fn deserialize_strength<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let strength = f32::deserialize(deserializer)?;
+    if strength < 0. {
+        return Err(D::Error::custom("the emission strength can't be negative"));
+    }
+
+    Ok(strength)
+}
+
+// light given off by the surface itself: seen by the camera, and lighting the
+// other objects with the path tracer
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Emission {
+    pub color: Color,
+    #[serde(default = "default_strength", deserialize_with = "deserialize_strength")]
+    pub strength: f32,
+}
+
+impl Emission {
+    pub fn new(color: Color, strength: f32) -> Self {
+        Self {
+            color,
+            strength: strength.max(0.0),
+        }
+    }
+
+    pub fn radiance(&self) -> Color {
+        self.color * self.strength
+    }
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+fn default_reflectance() -> f32 {
+    0.04
+}
+
+// metal / roughness model, the base color is the color (or pattern) of the material
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Pbr {
+    #[serde(default)]
+    pub metallic: f32, // 0 plastic, wood, stone... 1 metal, between 0 and 1
+    #[serde(default = "default_roughness")]
+    pub roughness: f32, // 0 polished, 1 rough, between 0 and 1
+    #[serde(default = "default_reflectance")]
+    pub reflectance: f32, // reflected at normal incidence by non metals, 0.04 for most of them
+}
+
+impl Default for Pbr {
+    fn default() -> Self {
+        Self {
+            metallic: 0.,
+            roughness: default_roughness(),
+            reflectance: default_reflectance(),
+        }
+    }
+}
+
+impl Pbr {
+    pub fn new(metallic: f32, roughness: f32) -> Self {
+        Self {
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.0, 1.0),
+            reflectance: default_reflectance(),
+        }
+    }
+
+    // closest look to the phong settings: the highlight gets as wide as with the
+    // shininess, the specular sets how much is reflected, mirrors become metals
+    pub fn from_phong(material: &Material) -> Self {
+        let alpha_squared = 2. / (material.shininess + 2.);
+
+        Self {
+            metallic: material.reflective.clamp(0.0, 1.0),
+            roughness: alpha_squared.powf(0.25).clamp(0.0, 1.0),
+            reflectance: (0.08 * material.specular).clamp(0.0, 1.0),
+        }
+    }
 }
 
 impl Default for Material {
@@ -27,6 +122,8 @@ impl Default for Material {
         let pattern = None;
         let refractive_index = 1.0;
         let transparency = 0.;
+        let pbr = None;
+        let emission = None;
 
         Self {
             shininess,
@@ -38,6 +135,8 @@ impl Default for Material {
             reflective,
             refractive_index,
             transparency,
+            pbr,
+            emission,
         }
     }
 }
@@ -83,6 +182,45 @@ impl Material {
         self
     }
 
+    pub fn transparency(mut self, transparency: f32) -> Self {
+        self.transparency = transparency.clamp(0.0, 1.0);
+
+        self
+    }
+
+    pub fn refractive_index(mut self, refractive_index: f32) -> Self {
+        self.refractive_index = refractive_index.max(1.0);
+
+        self
+    }
+
+    pub fn pbr(mut self, pbr: Pbr) -> Self {
+        self.pbr = Some(pbr);
+
+        self
+    }
+
+    pub fn emission(mut self, emission: Emission) -> Self {
+        self.emission = Some(emission);
+
+        self
+    }
+
+    // light given off by the surface, black for most of them
+    pub fn emitted(&self) -> Color {
+        match &self.emission {
+            Some(emission) => emission.radiance(),
+            None => Color::BLACK,
+        }
+    }
+
+    // switch a phong material to the metal / roughness model
+    pub fn to_pbr(mut self) -> Self {
+        self.pbr = Some(Pbr::from_phong(&self));
+
+        self
+    }
+
     //
     // pub fn pattern(mut self, pattern: Pattern) -> Self {
     //     self.pattern = Some(pattern);
@@ -78,6 +78,7 @@ impl Mul<Ray> for Matrix {
         Ray {
             origin: self.clone() * rhs.origin,
             direction: self.clone() * rhs.direction,
+            time: rhs.time,
         }
     }
 }
@@ -12,4 +12,20 @@ pub mod shapes;
 pub mod render;
 pub mod vector;
 pub mod world;
-pub mod pattern;
\ No newline at end of file
+pub mod pattern;
+pub mod noise;
+pub mod procedural;
+pub mod registry;
+pub mod pattern_kind;
+pub mod bounds;
+pub mod sampler;
+pub mod environment;
+pub mod brdf;
+pub mod film;
+pub mod settings;
+pub mod integrator;
+pub mod animation;
+pub mod camera_path;
+pub mod tonemap;
+pub mod aov;
+pub mod denoise;
@@ -1,5 +1,6 @@
 use crate::EPSILON;
-use crate::{Intersection, Material, Matrix, Point, Ray, Shape, Vector};
+use crate::structures::animation::value_at;
+use crate::{Color, ObjectAnimation, Intersection, Material, Matrix, Point, Ray, Shape, Vector};
 
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +17,11 @@ pub struct Object {
     pub world_to_local: Matrix,
     pub local_to_world: Matrix,
 
+    #[serde(default)]
+    pub motion: Option<Motion>, // where the object is when the shutter closes, for the motion blur
+    #[serde(default)]
+    pub animation: Option<ObjectAnimation>,
+
     // pub tex_img_name: Option<String>,
     // pub tex: Option
 }
@@ -32,15 +38,101 @@ impl Object {
             scale: Vector::new(1., 1., 1.),
             world_to_local: Matrix::identity(),
             local_to_world: Matrix::identity(),
+            motion: None,
+            animation: None,
         }
     }
 
+    pub fn motion(mut self, motion: Motion) -> Self {
+        self.motion = Some(motion);
+
+        self
+    }
+
+    pub fn animation(mut self, animation: ObjectAnimation) -> Self {
+        self.animation = Some(animation);
+
+        self
+    }
+
+    // puts the object where its keyframes say at a time in seconds, and where it
+    // will be a frame later for the motion blur, update() has to follow
+    pub fn animate(&mut self, time: f32, frame_duration: f32) {
+        let animation = match &self.animation {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        let position = value_at(&animation.position, time).unwrap_or(self.position);
+        let pitch = value_at(&animation.pitch, time).unwrap_or(self.pitch);
+        let yaw = value_at(&animation.yaw, time).unwrap_or(self.yaw);
+        let roll = value_at(&animation.roll, time).unwrap_or(self.roll);
+        let scale = value_at(&animation.scale, time).unwrap_or(self.scale);
+        let color = value_at(&animation.color, time).unwrap_or(self.material.color);
+
+        let end = time + frame_duration;
+        let motion = if animation.moves() && frame_duration > 0. {
+            Some(Motion {
+                position: value_at(&animation.position, end),
+                pitch: value_at(&animation.pitch, end),
+                yaw: value_at(&animation.yaw, end),
+                roll: value_at(&animation.roll, end),
+                scale: value_at(&animation.scale, end),
+            })
+        } else {
+            self.motion.clone()
+        };
+
+        self.position = position;
+        self.pitch = pitch;
+        self.yaw = yaw;
+        self.roll = roll;
+        self.scale = scale;
+        self.material.color = color;
+        self.motion = motion;
+    }
+
     pub fn material(mut self, material: Material) -> Self {
         self.material = material;
 
         self
     }
 
+    // color of the surface at a world point, from the pattern if there is one
+    pub fn color_at(&self, world_point: &Point) -> Color {
+        match &self.material.pattern {
+            Some(pattern) => pattern.stripe_at_object(self, world_point),
+            None => self.material.color,
+        }
+    }
+
+    // sphere around the object in world space, None when it has no end
+    pub fn bounding_sphere(&self) -> Option<(Point, f32)> {
+        let bounds = self.shape.bounds();
+        if !bounds.is_finite() {
+            return None;
+        }
+
+        // a moving object is somewhere between its two placements
+        let mut corners = bounds.corners().map(|c| self.local_to_world.clone() * c).to_vec();
+        if self.motion.is_some() {
+            let end = self.transform_at(1.);
+            corners.extend(bounds.corners().map(|c| end.clone() * c));
+        }
+
+        let mut center = Vector::new(0., 0., 0.);
+        for corner in &corners {
+            center += Vector::new(corner.x, corner.y, corner.z) * (1. / corners.len() as f32);
+        }
+        let center = Point::new(center.x, center.y, center.z);
+        let radius = corners
+            .iter()
+            .map(|c| (*c - center).magnitude())
+            .fold(0., f32::max);
+
+        Some((center, radius))
+    }
+
     pub fn update(&mut self) {
         let vt = Vector::new(self.position.x, self.position.y, self.position.z);
 
@@ -52,19 +144,72 @@ impl Object {
         self.world_to_local = self.local_to_world.inverse().unwrap();
     }
 
+    // local to world matrix at a moment of the shutter, the placement goes in a
+    // straight line from the object's own at 0 to the motion's at 1
+    pub fn transform_at(&self, time: f32) -> Matrix {
+        if self.motion.is_none() || time <= 0. {
+            return self.local_to_world.clone();
+        }
+
+        let (translation, rotation, scale) = self.placement_at(time);
+        Matrix::translation(translation) * rotation * Matrix::scaling(scale)
+    }
+
+    // world to local matrix at a moment of the shutter, put together from the
+    // parts of the placement, cheaper than inverting a matrix for every ray
+    pub fn inverse_transform_at(&self, time: f32) -> Option<Matrix> {
+        if self.motion.is_none() || time <= 0. {
+            return Some(self.world_to_local.clone());
+        }
+
+        let (translation, rotation, scale) = self.placement_at(time);
+        if scale.x == 0. || scale.y == 0. || scale.z == 0. {
+            return None;
+        }
+
+        let unscaling = Matrix::scaling(Vector::new(1. / scale.x, 1. / scale.y, 1. / scale.z));
+        Some(unscaling * rotation.transpose() * Matrix::translation(-translation))
+    }
+
+    fn placement_at(&self, time: f32) -> (Vector, Matrix, Vector) {
+        let motion = self.motion.clone().unwrap_or_default();
+
+        let t = time.min(1.);
+        let lerp = |start: f32, end: Option<f32>| start + (end.unwrap_or(start) - start) * t;
+
+        let end = motion.position.unwrap_or(self.position);
+        let position = self.position + (end - self.position) * t;
+        let scale = self.scale + (motion.scale.unwrap_or(self.scale) - self.scale) * t;
+        let rotation = Matrix::rotation(
+            lerp(self.pitch, motion.pitch),
+            lerp(self.yaw, motion.yaw),
+            lerp(self.roll, motion.roll),
+        );
+
+        (Vector::new(position.x, position.y, position.z), rotation, scale)
+    }
+
     pub fn intersect(&self, ray: &Ray, n1: f32) -> Option<Intersection> {
+        let (world_to_local, local_to_world) = match &self.motion {
+            Some(_) if ray.time > 0. => (
+                self.inverse_transform_at(ray.time)?,
+                self.transform_at(ray.time),
+            ),
+            _ => (self.world_to_local.clone(), self.local_to_world.clone()),
+        };
+
         // Transform ray to local space
-        let local_ray = self.world_to_local.clone() * ray.clone();
+        let local_ray = world_to_local * *ray;
 
         // Delegate to shape's local-space intersection logic
         if let Some(local_hit) = self.shape.intersect(local_ray) {
             // Transform hit data back to WORLD space
-            let world_point: Point = self.local_to_world.clone() * local_hit.point;
-            let world_normal: Vector = (self.local_to_world.clone() * local_hit.normal).normalize();
+            let world_point: Point = local_to_world.clone() * local_hit.point;
+            let world_normal: Vector = (local_to_world * local_hit.normal).normalize();
 
             let over_point = world_point + world_normal * EPSILON;
 
-            Some(Intersection::new(
+            let mut hit = Intersection::new(
                 self,
                 local_hit.t,
                 world_point,
@@ -73,7 +218,10 @@ impl Object {
                 over_point,
                 (ray.direction).reflect(&world_normal),
                 n1,
-            ))
+            );
+            hit.time = ray.time;
+
+            Some(hit)
         } else {
             None
         }
@@ -81,6 +229,47 @@ impl Object {
 
 }
 
+// placement of a moving object when the shutter closes, what is left out doesn't move
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Motion {
+    #[serde(default)]
+    pub position: Option<Point>,
+    #[serde(default)]
+    pub pitch: Option<f32>,
+    #[serde(default)]
+    pub yaw: Option<f32>,
+    #[serde(default)]
+    pub roll: Option<f32>,
+    #[serde(default)]
+    pub scale: Option<Vector>,
+}
+
+impl Motion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn position(mut self, position: Point) -> Self {
+        self.position = Some(position);
+
+        self
+    }
+
+    pub fn rotation(mut self, pitch: f32, yaw: f32, roll: f32) -> Self {
+        self.pitch = Some(pitch);
+        self.yaw = Some(yaw);
+        self.roll = Some(roll);
+
+        self
+    }
+
+    pub fn scale(mut self, scale: Vector) -> Self {
+        self.scale = Some(scale);
+
+        self
+    }
+}
+
 // this trait will be associate to Patterns, so maybe he deserve it own file ?
 pub trait Transform {
     fn rotate(&mut self, pitch: f32, yaw: f32, roll: f32);
@@ -1,4 +1,10 @@
-use crate::{Color, Matrix, Object, Point, Transform, Vector};
+use std::sync::Arc;
+
+use crate::structures::noise::fbm;
+use crate::structures::pattern_kind::{
+    builtin_kind, deserialize_kind, serialize_kind, Checker, Gradient, Ring,
+};
+use crate::{Color, Matrix, Object, PatternKind, Point, Procedural, Shape, Transform, Vector};
 
 use serde::{Deserialize, Serialize};
 
@@ -13,12 +19,120 @@ pub enum Axis {
     XYZ,
 }
 
+// a pattern slot is either a plain color or another pattern
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Paint {
+    Color(Color),
+    Pattern(Box<Pattern>),
+}
+
+impl Paint {
+    // point is in the parent pattern space, a nested pattern applies its own transform
+    pub fn color_at(&self, point: &Point) -> Color {
+        match self {
+            Paint::Color(color) => *color,
+            Paint::Pattern(pattern) => pattern.color_at(&(pattern.world_to_local.clone() * *point)),
+        }
+    }
+
+    // only evaluate the slot that is really needed
+    pub fn mix(a: &Paint, b: &Paint, point: &Point, weight: f32) -> Color {
+        if weight <= 0. {
+            a.color_at(point)
+        } else if weight >= 1. {
+            b.color_at(point)
+        } else {
+            let a = a.color_at(point);
+            a + (b.color_at(point) - a) * weight
+        }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Color(color)
+    }
+}
+
+impl From<Pattern> for Paint {
+    fn from(pattern: Pattern) -> Self {
+        Paint::Pattern(Box::new(pattern))
+    }
+}
+
+// combinator nodes replace the axis logic to merge the two slots
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum Combinator {
+    Mix(f32),           // constant weight, 0 is only a and 1 is only b
+    Mask(Box<Pattern>), // weight given by the luminance of the mask
+}
+
+impl Combinator {
+    pub fn weight_at(&self, point: &Point) -> f32 {
+        match self {
+            Combinator::Mix(weight) => weight.clamp(0., 1.),
+            Combinator::Mask(mask) => mask
+                .color_at(&(mask.world_to_local.clone() * *point))
+                .luminance()
+                .clamp(0., 1.),
+        }
+    }
+}
+
+// move the lookup point with some noise before evaluating the pattern
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Perturbation {
+    pub amount: f32,
+    #[serde(default = "default_perturbation_frequency")]
+    pub frequency: f32,
+    #[serde(default = "default_perturbation_octaves")]
+    pub octaves: u32,
+}
+
+fn default_perturbation_frequency() -> f32 {
+    1.0
+}
+
+fn default_perturbation_octaves() -> u32 {
+    3
+}
+
+impl Perturbation {
+    pub fn new(amount: f32) -> Self {
+        Self {
+            amount,
+            frequency: default_perturbation_frequency(),
+            octaves: default_perturbation_octaves(),
+        }
+    }
+
+    pub fn perturb(&self, point: &Point) -> Point {
+        let p = *point * self.frequency;
+
+        // offset the lookups so the three axes don't move together
+        let dx = fbm(&p, self.octaves, 2.0, 0.5);
+        let dy = fbm(&(p + Vector::new(5.2, 1.3, 2.8)), self.octaves, 2.0, 0.5);
+        let dz = fbm(&(p + Vector::new(1.7, 9.2, 3.4)), self.octaves, 2.0, 0.5);
+
+        *point + Vector::new(dx, dy, dz) * self.amount
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Pattern {
-    pub a: Color,
-    pub b: Color,
+    pub a: Paint,
+    pub b: Paint,
     pub axis: Axis,
     pub blending: bool,
+    #[serde(default, serialize_with = "serialize_kind", deserialize_with = "deserialize_kind")]
+    pub kind: Option<Arc<dyn PatternKind>>, // registered kind, win over axis, blending and procedural
+    #[serde(default)]
+    pub procedural: Option<Procedural>, // noise based texture, replace the axis stripes when set
+    #[serde(default)]
+    pub combinator: Option<Combinator>,
+    #[serde(default)]
+    pub perturbation: Option<Perturbation>,
 
     pub local_to_world: Matrix,
     pub world_to_local: Matrix,
@@ -30,12 +144,16 @@ pub struct Pattern {
 }
 
 impl Pattern {
-    pub fn new(color_a: Color, color_b: Color, axis: Axis, blending: bool) -> Pattern {
+    pub fn new(a: impl Into<Paint>, b: impl Into<Paint>, axis: Axis, blending: bool) -> Pattern {
         Pattern {
-            a: color_a,
-            b: color_b,
+            a: a.into(),
+            b: b.into(),
             axis,
             blending,
+            kind: None,
+            procedural: None,
+            combinator: None,
+            perturbation: None,
             local_to_world: Matrix::identity(),
             world_to_local: Matrix::identity(),
             scale: Vector::new(1., 1., 1.),
@@ -46,60 +164,89 @@ impl Pattern {
         }
     }
 
-    pub fn stripe_two_colors(&self, pos1: &f32, pos2: &f32) -> Color {
-        if (pos1 * pos1 + pos2 * pos2).sqrt().floor() % 2. == 0. {
-            self.a.clone()
-        } else {
-            self.b.clone()
-        }
+    pub fn kind(mut self, kind: Arc<dyn PatternKind>) -> Self {
+        self.kind = Some(kind);
+
+        self
     }
 
-    pub fn stripe_three_colors(&self, point: &Point) -> Color {
-        if (point.x.floor() + point.y.floor() + point.z.floor()) % 2. == 0. {
-            self.a.clone()
+    pub fn procedural(mut self, procedural: Procedural) -> Self {
+        self.procedural = Some(procedural);
+
+        self
+    }
+
+    pub fn combinator(mut self, combinator: Combinator) -> Self {
+        self.combinator = Some(combinator);
+
+        self
+    }
+
+    pub fn perturbation(mut self, perturbation: Perturbation) -> Self {
+        self.perturbation = Some(perturbation);
+
+        self
+    }
+
+    // the kind that is drawn: the registered one, the procedural one or the one given by the axis
+    pub fn kind_ref(&self) -> &dyn PatternKind {
+        if let Some(kind) = &self.kind {
+            kind.as_ref()
+        } else if let Some(procedural) = &self.procedural {
+            procedural
         } else {
-            self.b.clone()
+            builtin_kind(&self.axis, self.blending)
         }
     }
 
     pub fn stripe_at(&self, point: &Point) -> Color {
-        if self.axis == Axis::XYZ {
-            return self.stripe_three_colors(point);
-        } else if self.axis == Axis::XY {
-            return self.stripe_two_colors(&point.x, &point.y);
-        } else if self.axis == Axis::XZ {
-            return self.stripe_two_colors(&point.x, &point.z);
-        } else if self.axis == Axis::YZ {
-            return self.stripe_two_colors(&point.z, &point.y);
-        }
+        builtin_kind(&self.axis, false).color_at(point, &self.a, &self.b)
+    }
 
-        let x = if point.x < 0. {
-            (point.x.abs() + 1.) % 2.0
-        } else {
-            point.x % 2.0
-        };
+    // rings in the plane of the two coordinates, now drawn by Ring
+    pub fn stripe_two_colors(&self, pos1: &f32, pos2: &f32) -> Color {
+        let ring = Ring { plane: Axis::XY };
 
-        if x < 1.0 {
-            self.a.clone() // a verifier
-        } else {
-            self.b.clone()
-        }
+        ring.color_at(&Point::new(*pos1, *pos2, 0.), &self.a, &self.b)
+    }
+
+    // now drawn by Checker
+    pub fn stripe_three_colors(&self, point: &Point) -> Color {
+        Checker.color_at(point, &self.a, &self.b)
     }
 
     // should become object method
     pub fn stripe_at_object(&self, obj: &Object, world_point: &Point) -> Color {
         let obj_point = obj.world_to_local.clone() * *world_point;
+        // custom shapes are textured through their uv, as the point (u, v, 0)
+        let obj_point = match &obj.shape {
+            Shape::Custom(shape) => {
+                let (u, v) = shape.uv_at(&obj_point);
+                Point::new(u, v, 0.)
+            }
+            _ => obj_point,
+        };
         let pattern_point = self.world_to_local.clone() * obj_point;
 
-        if self.blending {
-            self.pattern_at(&pattern_point)
+        self.color_at(&pattern_point)
+    }
+
+    // color of the pattern tree at a point already in pattern space
+    pub fn color_at(&self, point: &Point) -> Color {
+        let point = match &self.perturbation {
+            Some(perturbation) => perturbation.perturb(point),
+            None => *point,
+        };
+
+        if let Some(combinator) = &self.combinator {
+            Paint::mix(&self.a, &self.b, &point, combinator.weight_at(&point))
         } else {
-            self.stripe_at(&pattern_point)
+            self.kind_ref().color_at(&point, &self.a, &self.b)
         }
     }
 
     pub fn pattern_at(&self, point: &Point) -> Color {
-        self.a + (self.b - self.a) * (point.x - point.x.floor())
+        Gradient.color_at(point, &self.a, &self.b)
     }
 
     fn update(&mut self) {
@@ -0,0 +1,266 @@
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
+
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::structures::registry::{Registry, tagged};
+use crate::{Axis, Color, Paint, Point, Procedural, Texture};
+
+// what a pattern draws, implement it to add your own procedural textures
+pub trait PatternKind: Debug + Send + Sync {
+    // name used in the registry and in the "type" tag of the scene files
+    fn name(&self) -> &str;
+
+    // between 0 (only slot a) and 1 (only slot b), point is in pattern space
+    fn weight_at(&self, point: &Point) -> f32;
+
+    // override it to produce colors that don't come from the slots
+    fn color_at(&self, point: &Point, a: &Paint, b: &Paint) -> Color {
+        Paint::mix(a, b, point, self.weight_at(point))
+    }
+
+    // written next to the "type" tag when the scene is saved
+    fn parameters(&self) -> Value {
+        Value::Null
+    }
+}
+
+// stripes along x, whatever the axis (as the first patterns did)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Stripe;
+
+impl PatternKind for Stripe {
+    fn name(&self) -> &str {
+        "stripe"
+    }
+
+    fn weight_at(&self, point: &Point) -> f32 {
+        let x = if point.x < 0. {
+            (point.x.abs() + 1.) % 2.0
+        } else {
+            point.x % 2.0
+        };
+
+        if x < 1.0 { 0. } else { 1. }
+    }
+}
+
+// concentric rings in a plane
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Ring {
+    #[serde(default = "default_ring_plane")]
+    pub plane: Axis,
+}
+
+fn default_ring_plane() -> Axis {
+    Axis::XZ
+}
+
+impl PatternKind for Ring {
+    fn name(&self) -> &str {
+        "ring"
+    }
+
+    fn weight_at(&self, point: &Point) -> f32 {
+        let (pos1, pos2) = match self.plane {
+            Axis::XY => (point.x, point.y),
+            Axis::YZ => (point.z, point.y),
+            _ => (point.x, point.z),
+        };
+
+        if (pos1 * pos1 + pos2 * pos2).sqrt().floor() % 2. == 0. {
+            0.
+        } else {
+            1.
+        }
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+// 3D checker
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Checker;
+
+impl PatternKind for Checker {
+    fn name(&self) -> &str {
+        "checker"
+    }
+
+    fn weight_at(&self, point: &Point) -> f32 {
+        if (point.x.floor() + point.y.floor() + point.z.floor()) % 2. == 0. {
+            0.
+        } else {
+            1.
+        }
+    }
+}
+
+// linear blend from a to b, repeated every unit along x
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Gradient;
+
+impl PatternKind for Gradient {
+    fn name(&self) -> &str {
+        "gradient"
+    }
+
+    fn weight_at(&self, point: &Point) -> f32 {
+        point.x - point.x.floor()
+    }
+}
+
+impl PatternKind for Procedural {
+    fn name(&self) -> &str {
+        match self.texture {
+            Texture::Noise => "noise",
+            Texture::Turbulence => "turbulence",
+            Texture::Marble => "marble",
+            Texture::Wood => "wood",
+            Texture::Clouds => "clouds",
+            Texture::Granite => "granite",
+        }
+    }
+
+    fn weight_at(&self, point: &Point) -> f32 {
+        self.value_at(point)
+    }
+
+    fn color_at(&self, point: &Point, a: &Paint, b: &Paint) -> Color {
+        let value = self.value_at(point);
+
+        if self.ramp.is_empty() {
+            Paint::mix(a, b, point, value)
+        } else {
+            // a ramp brings its own colors, the slots are not used
+            self.ramp_at(value, &Color::BLACK, &Color::BLACK)
+        }
+    }
+
+    fn parameters(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            map.remove("texture");
+        }
+
+        value
+    }
+}
+
+static STRIPE: Stripe = Stripe;
+static CHECKER: Checker = Checker;
+static GRADIENT: Gradient = Gradient;
+static RING_XY: Ring = Ring { plane: Axis::XY };
+static RING_XZ: Ring = Ring { plane: Axis::XZ };
+static RING_YZ: Ring = Ring { plane: Axis::YZ };
+
+// the kinds behind the axis and blending fields of the scene files
+pub fn builtin_kind(axis: &Axis, blending: bool) -> &'static dyn PatternKind {
+    if blending {
+        return &GRADIENT;
+    }
+
+    match axis {
+        Axis::XYZ => &CHECKER,
+        Axis::XY => &RING_XY,
+        Axis::XZ => &RING_XZ,
+        Axis::YZ => &RING_YZ,
+        _ => &STRIPE,
+    }
+}
+
+fn registry() -> &'static Registry<dyn PatternKind> {
+    static REGISTRY: OnceLock<Registry<dyn PatternKind>> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        let registry: Registry<dyn PatternKind> = Registry::new();
+
+        registry.register("stripe", Box::new(|_| Ok(Arc::new(Stripe))));
+        registry.register("checker", Box::new(|_| Ok(Arc::new(Checker))));
+        registry.register("gradient", Box::new(|_| Ok(Arc::new(Gradient))));
+        registry.register(
+            "ring",
+            Box::new(|value| {
+                let ring: Ring = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+                Ok(Arc::new(ring))
+            }),
+        );
+
+        let textures = [
+            ("noise", Texture::Noise),
+            ("turbulence", Texture::Turbulence),
+            ("marble", Texture::Marble),
+            ("wood", Texture::Wood),
+            ("clouds", Texture::Clouds),
+            ("granite", Texture::Granite),
+        ];
+        for (name, texture) in textures {
+            registry.register(
+                name,
+                Box::new(move |value| {
+                    let mut value = value.clone();
+                    if let Value::Object(map) = &mut value {
+                        map.insert(String::from("texture"), serde_json::to_value(&texture).unwrap());
+                    }
+                    let procedural: Procedural =
+                        serde_json::from_value(value).map_err(|e| e.to_string())?;
+                    Ok(Arc::new(procedural))
+                }),
+            );
+        }
+
+        registry
+    })
+}
+
+// make a custom kind usable from the scene files under `name`
+pub fn register_pattern<F>(name: &str, factory: F)
+where
+    F: Fn(&Value) -> Result<Arc<dyn PatternKind>, String> + Send + Sync + 'static,
+{
+    registry().register(name, Box::new(factory));
+}
+
+// shortcut for kinds that can be read directly from their json parameters
+pub fn register_pattern_type<T>(name: &str)
+where
+    T: PatternKind + DeserializeOwned + 'static,
+{
+    register_pattern(name, |value| {
+        let kind: T = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        Ok(Arc::new(kind))
+    });
+}
+
+pub fn registered_patterns() -> Vec<String> {
+    registry().names()
+}
+
+// build a kind from its scene file json, { "type": "name", ...parameters }
+pub fn create_pattern(value: &Value) -> Result<Arc<dyn PatternKind>, String> {
+    registry().create(value)
+}
+
+pub fn serialize_kind<S>(kind: &Option<Arc<dyn PatternKind>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match kind {
+        Some(kind) => tagged(kind.name(), kind.parameters()).serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_kind<'de, D>(deserializer: D) -> Result<Option<Arc<dyn PatternKind>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        Some(value) => create_pattern(&value).map(Some).map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
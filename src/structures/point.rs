@@ -2,7 +2,7 @@
 use crate::EPSILON;
 use crate::Vector;
 
-use std::ops::{Add, AddAssign, Div, DivAssign, Neg, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, Neg, Sub, SubAssign};
 
 use serde::{Deserialize, Serialize};
 
@@ -63,6 +63,14 @@ impl SubAssign<Vector> for Point {
     }
 }
 
+impl Mul<f32> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Point::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
 impl Div<f32> for Point {
     type Output = Point;
 
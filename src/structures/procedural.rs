@@ -0,0 +1,167 @@
+use crate::structures::noise::{fbm, turbulence};
+use crate::{Color, Point};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Texture {
+    Noise,
+    Turbulence,
+    Marble,
+    Wood,
+    Clouds,
+    Granite,
+}
+
+// one step of a color ramp, `at` is between 0 and 1
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ColorStop {
+    pub at: f32,
+    pub color: Color,
+}
+
+impl ColorStop {
+    pub fn new(at: f32, color: Color) -> Self {
+        Self { at, color }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Procedural {
+    pub texture: Texture,
+    #[serde(default = "default_octaves")]
+    pub octaves: u32,
+    #[serde(default = "default_frequency")]
+    pub frequency: f32,
+    #[serde(default = "default_lacunarity")]
+    pub lacunarity: f32, // frequency multiplier between two octaves
+    #[serde(default = "default_gain")]
+    pub gain: f32, // amplitude multiplier between two octaves
+    #[serde(default = "default_distortion")]
+    pub distortion: f32, // how much the noise bends the marble veins and wood rings
+    #[serde(default)]
+    pub ramp: Vec<ColorStop>, // when empty, the pattern colors a and b are used
+}
+
+fn default_octaves() -> u32 {
+    4
+}
+
+fn default_frequency() -> f32 {
+    1.0
+}
+
+fn default_lacunarity() -> f32 {
+    2.0
+}
+
+fn default_gain() -> f32 {
+    0.5
+}
+
+fn default_distortion() -> f32 {
+    5.0
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl Procedural {
+    pub fn new(texture: Texture) -> Self {
+        Self {
+            texture,
+            octaves: default_octaves(),
+            frequency: default_frequency(),
+            lacunarity: default_lacunarity(),
+            gain: default_gain(),
+            distortion: default_distortion(),
+            ramp: Vec::new(),
+        }
+    }
+
+    pub fn octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves.max(1);
+
+        self
+    }
+
+    pub fn frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+
+        self
+    }
+
+    pub fn distortion(mut self, distortion: f32) -> Self {
+        self.distortion = distortion;
+
+        self
+    }
+
+    pub fn ramp(mut self, ramp: Vec<ColorStop>) -> Self {
+        self.ramp = ramp;
+
+        self
+    }
+
+    // scalar value of the texture at the point (in pattern space), between 0 and 1
+    pub fn value_at(&self, point: &Point) -> f32 {
+        let p = *point * self.frequency;
+
+        let value = match self.texture {
+            Texture::Noise => fbm(&p, self.octaves, self.lacunarity, self.gain) * 0.5 + 0.5,
+            Texture::Turbulence => turbulence(&p, self.octaves, self.lacunarity, self.gain),
+            Texture::Marble => {
+                let turb = turbulence(&p, self.octaves, self.lacunarity, self.gain);
+                0.5 + 0.5 * (p.x + self.distortion * turb).sin()
+            }
+            Texture::Wood => {
+                let noise = fbm(&p, self.octaves, self.lacunarity, self.gain);
+                let rings = (p.x * p.x + p.z * p.z).sqrt() + self.distortion * 0.1 * noise;
+                rings - rings.floor()
+            }
+            Texture::Clouds => {
+                let noise = fbm(&p, self.octaves, self.lacunarity, self.gain) * 0.5 + 0.5;
+                smoothstep(0.35, 0.75, noise)
+            }
+            Texture::Granite => {
+                let turb = turbulence(&(p * 4.0), self.octaves, self.lacunarity, self.gain);
+                ((turb - 0.1) * 2.5).clamp(0.0, 1.0)
+            }
+        };
+
+        value.clamp(0.0, 1.0)
+    }
+
+    // map a value between 0 and 1 through the ramp, or from a to b without ramp
+    pub fn ramp_at(&self, value: f32, a: &Color, b: &Color) -> Color {
+        if self.ramp.is_empty() {
+            return *a + (*b - *a) * value;
+        }
+
+        let first = &self.ramp[0];
+        if value <= first.at {
+            return first.color;
+        }
+
+        for pair in self.ramp.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            if value <= end.at {
+                let span = end.at - start.at;
+                if span <= 0.0 {
+                    return end.color;
+                }
+                let t = (value - start.at) / span;
+                return start.color + (end.color - start.color) * t;
+            }
+        }
+
+        self.ramp[self.ramp.len() - 1].color
+    }
+
+    pub fn color_at(&self, point: &Point, a: &Color, b: &Color) -> Color {
+        self.ramp_at(self.value_at(point), a, b)
+    }
+}
@@ -1,15 +1,25 @@
-
 use crate::{Point, Vector};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    pub time: f32, // moment of the shutter, 0 when it opens and 1 at the end of the motion
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vector) -> Self {
-        Self { origin, direction }
+        Self {
+            origin,
+            direction,
+            time: 0.,
+        }
+    }
+
+    pub fn at_time(mut self, time: f32) -> Self {
+        self.time = time;
+
+        self
     }
 
     pub fn position(&self, t: f32) -> Point {
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use serde_json::Value;
+
+pub type Factory<T> = Box<dyn Fn(&Value) -> Result<Arc<T>, String> + Send + Sync>;
+
+// name -> constructor table, used to build user types from the "type" tag of the scene files
+pub struct Registry<T: ?Sized> {
+    factories: RwLock<HashMap<String, Arc<Factory<T>>>>,
+}
+
+impl<T: ?Sized> Default for Registry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> Registry<T> {
+    pub fn new() -> Self {
+        Self {
+            factories: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // registering an existing name replaces the previous factory
+    pub fn register(&self, name: &str, factory: Factory<T>) {
+        self.factories
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Arc::new(factory));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.read().unwrap().contains_key(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.factories.read().unwrap().keys().cloned().collect();
+        names.sort();
+
+        names
+    }
+
+    // the value is the whole json object, "type" included
+    pub fn create(&self, value: &Value) -> Result<Arc<T>, String> {
+        let name = value
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| String::from("missing \"type\" tag"))?;
+
+        // the lock is released before the call, a factory can use the registry too
+        let factory = self.factories.read().unwrap().get(name).cloned();
+        match factory {
+            Some(factory) => factory(value),
+            None => Err(format!("unknown type \"{}\"", name)),
+        }
+    }
+}
+
+// the json written in scene files: the parameters with the "type" tag added
+pub fn tagged(name: &str, parameters: Value) -> Value {
+    let mut map = match parameters {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    map.insert(String::from("type"), Value::String(name.to_string()));
+
+    Value::Object(map)
+}
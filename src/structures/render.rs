@@ -1,16 +1,23 @@
 
 // use minifb::{Key, Window};
-use minifb::{Key, Window, WindowOptions};
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use std::time::Instant;
 
 // use crate::{Camera, Canvas, Direction, World};
-use crate::{Camera, Canvas, World, Point, Vector, Color, Matrix, Ray, Direction, Intersection, Light};
+use crate::structures::sampler::Rng;
+use crate::{Camera, Canvas, World, Point, Color, Ray, Direction, Intersection, Light, LightKind, LightModel};
+use crate::structures::film::{contrast, contrast_mask};
+use crate::{Adaptive, Aov, CameraPath, Denoiser, Film, Frames, Integrator, NewCamera, NewCanvas, PathTracer, Projection, RenderSettings};
 
 pub struct Renderer {
-    pub window: Window,
+    pub window: Option<Window>, // None when rendering to a file
     pub canvas: Canvas,
     pub world: World,
     pub camera: Camera,
-    pub sky: Color,
+    pub settings: RenderSettings,
+    pub recording: Option<CameraPath>, // poses of the camera while flying in the window
+    pub passes: Vec<(Aov, Canvas)>, // filled by render_passes
+    light_films: Option<(Film, Film)>, // direct and reflected parts of the last update_image
     // pub size: (usize, usize),
     // enlever size pose pb avec la fonction render du projet d'origine
 
@@ -28,25 +35,9 @@ impl Renderer {
             }
         ) {
             Ok(window) => {
-                let camera = Camera::new(
-                    Point::new(0., 0., 10.),
-                    Vector::new(0., 0., -1.),
-                    canvas.width as f32 / canvas.height as f32,
-                    45f32.to_radians(),
-                    0.1,
-                    100.,
-                );
-                let mut new_world = world.clone();
-                for object in &mut new_world.objects {
-                    object.update();
-                }
-                Ok(Self {
-                    window,
-                    canvas,
-                    world: new_world,
-                    camera,
-                    sky: Color::new(0., 0., 0.), // a basculer dans le monde ?
-                })
+                let mut renderer = Self::headless(canvas, world);
+                renderer.window = Some(window);
+                Ok(renderer)
             }
             Err(e) => {
                 Err(e)
@@ -54,25 +45,71 @@ impl Renderer {
         }
     }
 
+    // no window, the image is only computed by update_image
+    pub fn headless(canvas: Canvas, world: World) -> Self {
+        let camera = Camera::from_settings(
+            &NewCamera::default(),
+            canvas.width as f32 / canvas.height as f32,
+        );
+        let mut new_world = world.clone();
+        for object in &mut new_world.objects {
+            object.update();
+        }
+
+        Self {
+            window: None,
+            canvas,
+            world: new_world,
+            camera,
+            settings: RenderSettings::default(),
+            recording: None,
+            passes: Vec::new(),
+            light_films: None,
+        }
+    }
+
+    // the scene and the camera as they are at a frame of the animation
+    pub fn set_frame(&mut self, world: &World, camera: &NewCamera, frames: &Frames, frame: usize) {
+        let time = frames.time(frame);
+        let aspect = self.canvas.width as f32 / self.canvas.height as f32;
+
+        self.world = world.at_time(time, frames.duration());
+        self.camera = Camera::from_settings(&camera.at_time(time), aspect);
+    }
+
 // shadow and light
 
     pub fn is_shadowed(&self, point: &Point, light: &Light) -> bool {
-        let v = light.position - *point;
-        let distance = v.magnitude();
-        let direction = v.normalize();
+        let distance = light.distance_from(point);
+        let direction = light.direction_from(point);
+
+        self.world.transmittance(point, &direction, distance).is_black()
+    }
 
-        let r = Ray::new(*point, direction);
+    // light reaching the point at a time of the shutter, several shadow rays for the area lights
+    pub fn visibility(&self, point: &Point, light: &Light, time: f32) -> Color {
+        match light.kind {
+            LightKind::Area { .. } => {
+                let mut rng = Rng::from_point(point);
+                let samples = light.sample_positions(point, &mut rng);
+                let mut visibility = Color::BLACK;
+
+                for sample in &samples {
+                    let v = *sample - *point;
+                    visibility += self
+                        .world
+                        .transmittance_at(point, &v.normalize(), v.magnitude(), time);
+                }
 
-        let intersection = self.world.intersect(&r, 1.);
+                visibility * (1. / samples.len() as f32)
+            }
+            _ => {
+                let distance = light.distance_from(point);
+                let direction = light.direction_from(point);
 
-        if intersection.is_some() {
-            let h = intersection.unwrap();
-            if h.t < distance {
-                return true;
+                self.world.transmittance_at(point, &direction, distance, time)
             }
         }
-
-        false
     }
 
     pub fn shade_it(&self, comps: &Intersection) -> Color {
@@ -80,24 +117,71 @@ impl Renderer {
         let light_number = self.world.lights.len();
 
         for light in &self.world.lights {
-            let shadowed = self.is_shadowed(&comps.over_point, light);
+            // no shadow ray for the points outside of a spot cone
+            let visibility = if light.falloff(&comps.over_point) <= 0. {
+                Color::BLACK
+            } else {
+                self.visibility(&comps.over_point, light, comps.time)
+            };
+
+            color += match self.world.light_model {
+                // the ambient doesn't fade with the distance, it is added once below
+                LightModel::Physical => {
+                    World::reflected_light(
+                        comps.object,
+                        light,
+                        &comps.over_point,
+                        &comps.hit_normal,
+                        &comps.normal,
+                        visibility,
+                    ) * light.attenuation_at(&comps.over_point)
+                }
+                LightModel::Legacy => {
+                    World::lighting_with_visibility(
+                        comps.object,
+                        light,
+                        &comps.over_point,
+                        &comps.hit_normal,
+                        &comps.normal,
+                        visibility,
+                    ) * (1.0 / light_number as f32)
+                }
+            };
+        }
+
+        if self.world.light_model == LightModel::Physical {
+            color += self.world.ambient_at(comps.object, &comps.over_point);
+        }
 
-            let temporary_color = World::lighting(
-                &comps.object,
-                light,
+        // shadow rays toward the map at every hit, only when asked for
+        if self.settings.environment_lighting {
+            color += self.world.image_based_lighting(
+                comps.object,
                 &comps.over_point,
                 &comps.hit_normal,
                 &comps.normal,
-                shadowed,
+                comps.time,
             );
-
-            color += temporary_color * (1.0 / light_number as f32);
         }
 
         color
     }
 
+    // the flat ambient of the first maps, added to every hit on top of the one of
+    // the materials, shade_it takes care of it in the physical model
+    fn legacy_ambient(&self) -> Color {
+        match self.world.light_model {
+            LightModel::Legacy => self.world.ambient.light(),
+            LightModel::Physical => Color::BLACK,
+        }
+    }
+
     pub fn get_phong_color(&self, initial_hit: &Intersection, ) -> Color {
+        self.shade_it(initial_hit) + self.reflected_color(initial_hit)
+    }
+
+    // light of the mirror bounces from the hit
+    pub fn reflected_color(&self, initial_hit: &Intersection) -> Color {
         let mut reflected_color = Color::new(0.0, 0.0, 0.0);
         let mut first_hit = initial_hit.clone();
         let mut factor = 1.0;
@@ -106,12 +190,15 @@ impl Renderer {
 
         if first_hit.object.material.reflective > 0. {
             for _ in 0..1 {
-                let reflected_ray = Ray::new(first_hit.point, first_hit.reflectv);
+                // from above the surface, the mirror would find itself first
+                let reflected_ray = Ray::new(first_hit.over_point, first_hit.reflectv).at_time(first_hit.time);
 
                 let reflected_hit = self.world.intersect(&reflected_ray, 1.);
                 if reflected_hit.is_some() {
                     let reflected_inter = reflected_hit.unwrap();
-                    reflected_color += self.shade_it(&reflected_inter)
+                    // an emitter seen in the mirror shows its own light too
+                    reflected_color += (self.shade_it(&reflected_inter)
+                        + reflected_inter.object.material.emitted())
                         * first_hit.object.material.reflective
                         * factor;
                     factor = factor * 0.20;
@@ -121,64 +208,345 @@ impl Renderer {
                 }
             }
         }
-        
-        let color = self.shade_it(&initial_hit) + reflected_color;
-        color
+
+        reflected_color
     }
 
     pub fn get_pixel(&mut self, ray: &Ray) -> Color {
+        self.trace(ray, &mut Rng::new(0)).0
+    }
+
+    // light seen along the ray, straight from the lights and after bounces, and
+    // the object it hit first
+    pub fn trace_split(&self, ray: &Ray, rng: &mut Rng) -> (Color, Color, Option<usize>) {
+        let hit = self.world.intersect(ray, 1.);
+        let first_hit = hit.as_ref().map(|hit| hit.object_id());
+        let (direct, reflected) = self.trace_from(ray, hit, rng);
+
+        (direct, reflected, first_hit)
+    }
+
+    // the same from the first hit of the ray, already found by the caller
+    fn trace_from(&self, ray: &Ray, hit: Option<Intersection>, rng: &mut Rng) -> (Color, Color) {
+        if self.settings.integrator == Integrator::PathTracer {
+            return PathTracer::new(self.settings.max_depth).trace_from(&self.world, ray, hit, rng);
+        }
+
+        match hit {
+            Some(inter) => {
+                let direct = self.shade_it(&inter)
+                    + self.legacy_ambient()
+                    + inter.object.material.emitted();
+                (direct, self.reflected_color(&inter))
+            }
+            None => (self.world.background.color_at(&ray.direction), Color::BLACK),
+        }
+    }
+
+    // color seen along the ray and the object it hit first
+    pub fn trace(&self, ray: &Ray, rng: &mut Rng) -> (Color, Option<usize>) {
+        if self.settings.integrator == Integrator::PathTracer {
+            return PathTracer::new(self.settings.max_depth).trace(&self.world, ray, rng);
+        }
+
         let hit: Option<Intersection> = self.world.intersect(ray, 1.);
         match hit {
             Some(inter) => {
                 // eprintln!("Je tombe sur l'obj {:?} pos: {:?}", inter.object.material.color, inter.object.position);
                 // inter.object.material.color//+ Color::new(0.1, 0.1, 0.1)
-                self.get_phong_color(&inter) + Color::new(0.1, 0.1, 0.1)
+                let color = self.get_phong_color(&inter)
+                    + self.legacy_ambient()
+                    + inter.object.material.emitted();
+                (color, Some(inter.object_id()))
             }
             None => {
 
-                self.sky
+                (self.world.background.color_at(&ray.direction), None)
             }
         }
     }
 
     pub fn update_image(&mut self) {
+        if self.camera.autofocus {
+            self.autofocus();
+        }
 
-        let view = Matrix::view(
-            self.camera.position,
-            self.camera.position + self.camera.direction(),
-            Vector::new(0., 1., 0.),
-        );
+        let width = self.canvas.width;
+        let height = self.canvas.height;
+        let split = self.settings.aovs.iter().any(|aov| aov.is_light());
+        let mut films = Films::new(width, height, &self.settings, split);
+        if self.settings.denoise.is_some() {
+            films.guides = Some(vec![None; width * height]);
+        }
 
-        let projection = Matrix::projection(self.camera.fov, self.camera.aspect, self.camera.near, self.camera.far);
+        match &self.settings.adaptive {
+            Some(adaptive) => self.sample_adaptive(&mut films, adaptive),
+            None => self.sample_uniform(&mut films),
+        }
 
-        let view_proj = projection * view;
-        let inv_view_proj = view_proj.inverse().unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                self.canvas.write(x, y, films.image.pixel(x, y));
+            }
+        }
+        self.light_films = films.light;
+
+        // the light passes stay as sampled, they still add up to the image before
+        // the denoiser
+        if let (Some(denoiser), Some(guides)) = (&self.settings.denoise, films.guides) {
+            let mut albedo = Canvas::new(NewCanvas { width, height });
+            let mut normal = Canvas::new(NewCanvas { width, height });
+            let mut depth = Canvas::new(NewCanvas { width, height });
+            for (index, guide) in guides.iter().enumerate() {
+                let [a, n, z] = guide.unwrap_or([Color::BLACK, Color::BLACK, Color::BLACK]);
+                albedo.write(index % width, index / width, a);
+                normal.write(index % width, index / width, n);
+                depth.write(index % width, index / width, z);
+            }
+
+            self.canvas = denoiser.apply(&self.canvas, &albedo, &normal, &depth);
+        }
+    }
+
+    // the buffers asked in the settings, for the frame of the last update_image
+    pub fn render_passes(&mut self) {
+        let aovs = self.settings.aovs.clone();
+        let mut passes = self.data_passes(&aovs);
+
+        if let Some((direct, reflected)) = &self.light_films {
+            for (aov, pass) in &mut passes {
+                let film = match aov {
+                    Aov::Direct => direct,
+                    Aov::Reflected => reflected,
+                    _ => continue,
+                };
+                for y in 0..pass.height {
+                    for x in 0..pass.width {
+                        pass.write(x, y, film.pixel(x, y));
+                    }
+                }
+            }
+        }
+
+        self.passes = passes;
+    }
+
+    // what the ray through each pixel center hits first, sharp and at the
+    // opening of the shutter, black for the light passes
+    fn data_passes(&self, aovs: &[Aov]) -> Vec<(Aov, Canvas)> {
+        let width = self.canvas.width;
+        let height = self.canvas.height;
+        let mut passes: Vec<(Aov, Canvas)> = aovs
+            .iter()
+            .map(|aov| (*aov, Canvas::new(NewCanvas { width, height })))
+            .collect();
+
+        for y in 0..height {
+            for x in 0..width {
+                let center = self
+                    .camera
+                    .ray((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32)
+                    .map(|ray| ray.at_time(self.camera.shutter_open));
+                let hit = center.as_ref().and_then(|ray| self.world.intersect(ray, 1.));
+
+                for (aov, pass) in &mut passes {
+                    pass.write(x, y, self.data_value(aov, center.as_ref(), hit.as_ref()));
+                }
+            }
+        }
+
+        passes
+    }
+
+    // value of a data pass for a ray and its first hit, black for the light passes
+    fn data_value(&self, aov: &Aov, ray: Option<&Ray>, hit: Option<&Intersection>) -> Color {
+        match (aov, hit, ray) {
+            (Aov::Depth, Some(hit), Some(ray)) => {
+                let depth = (hit.point - ray.origin).magnitude();
+                Color::new(depth, depth, depth)
+            }
+            (Aov::Depth, _, _) => Color::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            (Aov::Normal, Some(hit), _) => Color::new(hit.normal.x, hit.normal.y, hit.normal.z),
+            (Aov::Albedo, Some(hit), _) => hit.object.color_at(&hit.point),
+            (Aov::Albedo, None, Some(ray)) => self.world.background.color_at(&ray.direction),
+            (Aov::ObjectId, Some(hit), _) => {
+                let index = self
+                    .world
+                    .objects
+                    .iter()
+                    .position(|object| std::ptr::eq(object, hit.object))
+                    .map_or(0., |index| index as f32 + 1.);
+                Color::new(index, index, index)
+            }
+            _ => Color::BLACK,
+        }
+    }
+
+    // the same number of samples in every pixel
+    fn sample_uniform(&self, films: &mut Films) {
+        let width = self.canvas.width;
 
         for y in 0..self.canvas.height {
-            let ndc_y = 1.0 - 2.0 * ((self.canvas.height - y) as f32 + 0.5) / self.canvas.height as f32;
+            for x in 0..width {
+                // one sequence per pixel, the same at each frame
+                let mut rng = Rng::new((y * width + x) as u64);
+                let offsets = self.settings.sampler.offsets(self.settings.samples, &mut rng);
 
-            for x in 0..self.canvas.width {
-                let ndc_x = 2.0 * (x as f32 + 0.5) / self.canvas.width as f32 - 1.0;
+                for (dx, dy) in offsets {
+                    let px = x as f32 + dx;
+                    let py = y as f32 + dy;
 
-                let origin = inv_view_proj.clone() * Point::new(ndc_x, ndc_y, -1.0);
-                let target = inv_view_proj.clone() * Point::new(ndc_x, ndc_y, 1.0);
+                    let guided = films.needs_guide(x, y);
+                    films.add_sample(px, py, self.sample(px, py, &mut rng, guided));
+                }
+            }
+        }
+    }
 
-                let direction = (target - origin).normalize();
+    // one sample at each pixel center, then more in the pixels on an edge or next
+    // to one, by batches until they agree with the first sample or the budget is spent
+    fn sample_adaptive(&self, films: &mut Films, adaptive: &Adaptive) {
+        let width = self.canvas.width;
+        let height = self.canvas.height;
+        let mut colors = Vec::with_capacity(width * height);
+        let mut ids = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut rng = Rng::new((y * width + x) as u64);
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+
+                let sample = self.sample(px, py, &mut rng, films.needs_guide(x, y));
+                colors.push(sample.color());
+                ids.push(sample.id);
+                films.add_sample(px, py, sample);
+            }
+        }
 
-                let ray = Ray::new(Point::new(origin.x, origin.y, origin.z), direction);
-                let color = self.get_pixel(&ray);
-                self.canvas.write(x as usize, y as usize, color);
-                // a completer ici
+        if adaptive.max_samples <= 1 {
+            return;
+        }
+
+        let mask = contrast_mask(width, height, &colors, &ids, adaptive.threshold);
+
+        for (index, _) in mask.iter().enumerate().filter(|(_, edge)| **edge) {
+            let x = index % width;
+            let y = index / width;
+            // past the seeds of the first pass, its sample would come back otherwise
+            let mut rng = Rng::new((width * height + index) as u64);
+            // the center is already one of the budget
+            let offsets = self
+                .settings
+                .sampler
+                .shuffled_offsets(adaptive.max_samples - 1, &mut rng);
+
+            for batch in offsets.chunks(4) {
+                let mut spread: f32 = 0.;
+
+                for (dx, dy) in batch {
+                    let px = x as f32 + dx;
+                    let py = y as f32 + dy;
+
+                    let sample = self.sample(px, py, &mut rng, false);
+                    spread = spread.max(contrast(&sample.color(), &colors[index]));
+                    films.add_sample(px, py, sample);
+                }
+
+                if spread <= adaptive.threshold {
+                    break;
+                }
+            }
+        }
+    }
+
+    // sharp on the first object under the center of the screen, unchanged if there is none
+    fn autofocus(&mut self) {
+        let center = match self.camera.view_ray(0.5, 0.5) {
+            Some(ray) => ray.at_time(self.camera.shutter_open),
+            None => return,
+        };
+
+        if let Some(hit) = self.world.intersect(&center, 1.) {
+            let point = hit.point;
+            self.camera.focus_on(&point);
+        }
+    }
+
+    // what is seen through a point of the canvas given in pixels, black where the
+    // projection doesn't cover the image
+    fn sample(&self, x: f32, y: f32, rng: &mut Rng, guided: bool) -> Sample {
+        let ray = match self.camera_ray(x, y, rng) {
+            Some(ray) => ray,
+            None => {
+                return Sample {
+                    direct: Color::BLACK,
+                    reflected: Color::BLACK,
+                    id: None,
+                    guide: guided.then(|| self.guide(None, None)),
+                };
             }
+        };
+
+        let hit = self.world.intersect(&ray, 1.);
+        let id = hit.as_ref().map(|hit| hit.object_id());
+        let guide = guided.then(|| self.guide(Some(&ray), hit.as_ref()));
+        let (direct, reflected) = self.trace_from(&ray, hit, rng);
+
+        Sample {
+            direct,
+            reflected,
+            id,
+            guide,
+        }
+    }
+
+    // albedo, normal and depth of the first hit, for the denoiser
+    fn guide(&self, ray: Option<&Ray>, hit: Option<&Intersection>) -> [Color; 3] {
+        [Aov::Albedo, Aov::Normal, Aov::Depth].map(|aov| self.data_value(&aov, ray, hit))
+    }
+
+    // ray through a point of the canvas, from a random point of the lens and at a
+    // random moment while the shutter is open
+    // only the perspective camera has a lens, the others are always sharp
+    fn camera_ray(&self, x: f32, y: f32, rng: &mut Rng) -> Option<Ray> {
+        let u = x / self.canvas.width as f32;
+        let v = y / self.canvas.height as f32;
+        let mut ray = self.camera.ray(u, v)?;
+
+        if self.camera.aperture > 0. && self.camera.projection == Projection::Perspective {
+            ray = self.camera.lens_ray(&ray, rng.next_f32(), rng.next_f32());
+        }
+
+        if self.camera.shutter_close > self.camera.shutter_open {
+            Some(ray.at_time(self.camera.shutter_time(rng.next_f32())))
+        } else {
+            Some(ray.at_time(self.camera.shutter_open))
         }
     }
 
+    // shows the image until the window is closed, nothing to do without one
     pub fn render(&mut self) -> Result<u32, minifb::Error> {
-        while self.window.is_open() {
-            if self.window.is_key_down(Key::Escape) {
+        let mut window = match self.window.take() {
+            Some(window) => window,
+            None => return Ok(0),
+        };
+
+        let start = Instant::now();
+        // the denoiser that N swaps with the one of the settings
+        let mut parked = match self.settings.denoise {
+            Some(_) => None,
+            None => Some(Denoiser::default()),
+        };
+
+        while window.is_open() {
+            if window.is_key_down(Key::Escape) {
                 break;
             }
-            let current_size = self.window.get_size();
+            if window.is_key_pressed(Key::N, KeyRepeat::No) {
+                std::mem::swap(&mut self.settings.denoise, &mut parked);
+            }
+            let current_size = window.get_size();
             let size = (self.canvas.width, self.canvas.height);
 
             if size != current_size {
@@ -186,39 +554,42 @@ impl Renderer {
                 self.camera.resize(size.0 as f32 / size.1 as f32);
             }
 
-            if self.window.is_key_down(Key::A) {
+            if window.is_key_down(Key::A) {
                 self.camera.translate(Direction::Left);
             }
-            if self.window.is_key_down(Key::D) {
+            if window.is_key_down(Key::D) {
                 self.camera.translate(Direction::Right);
             }
-            if self.window.is_key_down(Key::W) {
+            if window.is_key_down(Key::W) {
                 self.camera.translate(Direction::Forward);
             }
-            if self.window.is_key_down(Key::S) {
+            if window.is_key_down(Key::S) {
                 self.camera.translate(Direction::Backward);
             }
 
-            if self.window.is_key_down(Key::Up) {
+            if window.is_key_down(Key::Up) {
                 self.camera.rotate_x(1.);
             }
-            if self.window.is_key_down(Key::Down) {
+            if window.is_key_down(Key::Down) {
                 self.camera.rotate_x(-1.);
             }
 
-            if self.window.is_key_down(Key::Right) {
+            if window.is_key_down(Key::Right) {
                 self.camera.rotate_y(1.);
             }
-            if self.window.is_key_down(Key::Left) {
+            if window.is_key_down(Key::Left) {
                 self.camera.rotate_y(-1.);
             }
 
             self.camera.update();
+            if let Some(recording) = &mut self.recording {
+                recording.record(&self.camera, start.elapsed().as_secs_f32());
+            }
 
             self.update_image();
 
-            let buffer = self.canvas.pixels();
-            match self.window
+            let buffer = self.canvas.pixels(&self.settings.display);
+            match window
                 .update_with_buffer(&buffer, current_size.0, current_size.1) {
                     Ok(_) => {
 
@@ -229,8 +600,64 @@ impl Renderer {
                     }
                 };
         }
+        self.window = Some(window);
         Ok(0)
 
     }
 
 }
+
+// what a camera ray brings back
+struct Sample {
+    direct: Color,
+    reflected: Color,
+    id: Option<usize>,         // of the object hit first
+    guide: Option<[Color; 3]>, // albedo, normal and depth of the first hit
+}
+
+impl Sample {
+    fn color(&self) -> Color {
+        self.direct + self.reflected
+    }
+}
+
+// the image being sampled, its direct and reflected parts when the passes ask
+// for them, and the guides of the denoiser from the first sample of each pixel
+struct Films {
+    image: Film,
+    light: Option<(Film, Film)>,
+    guides: Option<Vec<Option<[Color; 3]>>>,
+}
+
+impl Films {
+    fn new(width: usize, height: usize, settings: &RenderSettings, split: bool) -> Self {
+        let film = || Film::new(width, height, settings.filter, settings.filter_radius());
+
+        Self {
+            image: film(),
+            light: split.then(|| (film(), film())),
+            guides: None,
+        }
+    }
+
+    fn needs_guide(&self, x: usize, y: usize) -> bool {
+        self.guides
+            .as_ref()
+            .is_some_and(|guides| guides[y * self.image.width + x].is_none())
+    }
+
+    fn add_sample(&mut self, x: f32, y: f32, sample: Sample) {
+        self.image.add_sample(x, y, sample.color());
+
+        if let Some((direct, reflected)) = &mut self.light {
+            direct.add_sample(x, y, sample.direct);
+            reflected.add_sample(x, y, sample.reflected);
+        }
+
+        if let (Some(guides), Some(guide)) = (&mut self.guides, sample.guide) {
+            let px = (x.max(0.) as usize).min(self.image.width - 1);
+            let py = (y.max(0.) as usize).min(self.image.height - 1);
+            guides[py * self.image.width + px].get_or_insert(guide);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Point, Vector};
+
+// small pcg32 generator, enough for sampling and reproducible from a seed
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+
+        rng
+    }
+
+    // same point, same sequence: the image doesn't flicker between two frames
+    pub fn from_point(point: &Point) -> Self {
+        let seed = (point.x.to_bits() as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (point.y.to_bits() as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (point.z.to_bits() as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+        Rng::new(seed)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+
+        xorshifted.rotate_right(rot)
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+// jittered grid of n * n samples in the unit square
+pub fn stratified(n: usize, rng: &mut Rng) -> Vec<(f32, f32)> {
+    let mut samples = Vec::with_capacity(n * n);
+    let size = n as f32;
+
+    for j in 0..n {
+        for i in 0..n {
+            samples.push((
+                (i as f32 + rng.next_f32()) / size,
+                (j as f32 + rng.next_f32()) / size,
+            ));
+        }
+    }
+
+    samples
+}
+
+// where the samples go inside a pixel
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum PixelSampler {
+    #[default]
+    Stratified, // center of the cells of a grid, always the same
+    Jittered,   // random point in each cell of the grid
+    Halton,     // low discrepancy sequence, any number of samples
+}
+
+impl PixelSampler {
+    // at least `count` offsets in the unit square, the grids round up to a square number
+    pub fn offsets(&self, count: usize, rng: &mut Rng) -> Vec<(f32, f32)> {
+        let count = count.max(1);
+        let n = (count as f32).sqrt().ceil() as usize;
+
+        match self {
+            PixelSampler::Stratified => {
+                let size = n as f32;
+                (0..n * n)
+                    .map(|i| (((i % n) as f32 + 0.5) / size, ((i / n) as f32 + 0.5) / size))
+                    .collect()
+            }
+            PixelSampler::Jittered => stratified(n, rng),
+            PixelSampler::Halton => {
+                // shifted randomly so the pixels don't all share the same pattern
+                let (dx, dy) = (rng.next_f32(), rng.next_f32());
+                (1..=count)
+                    .map(|i| ((halton(i, 2) + dx) % 1., (halton(i, 3) + dy) % 1.))
+                    .collect()
+            }
+        }
+    }
+
+    // exactly `count` offsets, the cells of the grids in a random order so that
+    // any run of them spreads over the whole pixel
+    pub fn shuffled_offsets(&self, count: usize, rng: &mut Rng) -> Vec<(f32, f32)> {
+        let mut offsets = self.offsets(count, rng);
+
+        if *self != PixelSampler::Halton {
+            for i in (1..offsets.len()).rev() {
+                let j = (rng.next_u32() as usize) % (i + 1);
+                offsets.swap(i, j);
+            }
+        }
+        offsets.truncate(count);
+
+        offsets
+    }
+}
+
+impl FromStr for PixelSampler {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stratified" => Ok(PixelSampler::Stratified),
+            "jittered" => Ok(PixelSampler::Jittered),
+            "halton" => Ok(PixelSampler::Halton),
+            _ => Err(format!("unknown sampler '{}', expected stratified, jittered or halton", s)),
+        }
+    }
+}
+
+// index written in `base` and mirrored after the decimal point
+pub fn halton(index: usize, base: usize) -> f32 {
+    let mut result = 0.;
+    let mut fraction = 1. / base as f32;
+    let mut i = index;
+
+    while i > 0 {
+        result += (i % base) as f32 * fraction;
+        i /= base;
+        fraction /= base as f32;
+    }
+
+    result
+}
+
+// two unit vectors perpendicular to the normal and to each other
+pub fn basis(normal: &Vector) -> (Vector, Vector) {
+    let helper = if normal.x.abs() > 0.9 {
+        Vector::new(0., 1., 0.)
+    } else {
+        Vector::new(1., 0., 0.)
+    };
+    let tangent = helper.cross(normal).normalize();
+    let bitangent = normal.cross(&tangent);
+
+    (tangent, bitangent)
+}
+
+// direction around `axis`, denser where cos(angle)^exponent is high
+// exponent 1 is the cosine weighted hemisphere of the diffuse surfaces
+pub fn power_cosine(axis: &Vector, exponent: f32, u: f32, v: f32) -> Vector {
+    let (tangent, bitangent) = basis(axis);
+    let cos_theta = (1. - u).powf(1. / (exponent + 1.));
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = 2. * PI * v;
+
+    tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + *axis * cos_theta
+}
+
+// density of power_cosine, per unit of solid angle
+pub fn power_cosine_pdf(axis: &Vector, exponent: f32, direction: &Vector) -> f32 {
+    let cos_theta = axis.dot(direction);
+    if cos_theta <= 0. {
+        return 0.;
+    }
+
+    (exponent + 1.) / (2. * PI) * cos_theta.powf(exponent)
+}
+
+// weight of a sample when two strategies could have produced it
+pub fn power_heuristic(pdf: f32, other: f32) -> f32 {
+    let a = pdf * pdf;
+    let b = other * other;
+    if a + b <= 0. {
+        return 0.;
+    }
+
+    a / (a + b)
+}
+
+// point of the unit disk, the square is squeezed in rings so the strata stay even
+pub fn concentric_disk(u: f32, v: f32) -> (f32, f32) {
+    let a = 2. * u - 1.;
+    let b = 2. * v - 1.;
+    if a == 0. && b == 0. {
+        return (0., 0.);
+    }
+
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, PI / 4. * (b / a))
+    } else {
+        (b, PI / 2. - PI / 4. * (a / b))
+    };
+
+    (r * theta.cos(), r * theta.sin())
+}
+
+// direction inside the cone around `axis` whose half angle has cos_max as cosine,
+// all equally likely, the pdf is 1 / (2 pi (1 - cos_max))
+pub fn uniform_cone(axis: &Vector, cos_max: f32, u: f32, v: f32) -> Vector {
+    let (tangent, bitangent) = basis(axis);
+    let cos_theta = 1. - u * (1. - cos_max);
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = 2. * PI * v;
+
+    tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + *axis * cos_theta
+}
+
+// picks indices with a probability proportional to their weight
+#[derive(Debug, Clone, Default)]
+pub struct Distribution {
+    cdf: Vec<f32>,
+    total: f32,
+}
+
+impl Distribution {
+    pub fn new(weights: &[f32]) -> Self {
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        let mut total = 0.;
+
+        cdf.push(0.);
+        for weight in weights {
+            total += weight.max(0.);
+            cdf.push(total);
+        }
+
+        Self { cdf, total }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cdf.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total <= 0.
+    }
+
+    pub fn total(&self) -> f32 {
+        self.total
+    }
+
+    // index for u in [0, 1), and where u fell inside it (between 0 and 1)
+    pub fn sample(&self, u: f32) -> (usize, f32) {
+        let target = u * self.total;
+        let index = self
+            .cdf
+            .partition_point(|c| *c <= target)
+            .clamp(1, self.len())
+            - 1;
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if width > 0. {
+            ((target - self.cdf[index]) / width).clamp(0., 1.)
+        } else {
+            0.5
+        };
+
+        (index, offset)
+    }
+
+    pub fn probability(&self, index: usize) -> f32 {
+        if self.total <= 0. || index >= self.len() {
+            return 0.;
+        }
+
+        (self.cdf[index + 1] - self.cdf[index]) / self.total
+    }
+}
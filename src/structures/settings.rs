@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Aov, Denoiser, DisplaySettings, Filter, Integrator, PixelSampler};
+
+fn default_samples() -> usize {
+    1
+}
+
+fn default_max_depth() -> usize {
+    8
+}
+
+fn default_threshold() -> f32 {
+    0.1
+}
+
+fn default_max_samples() -> usize {
+    16
+}
+
+// one sample per pixel first, then more only where the picture changes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Adaptive {
+    #[serde(default = "default_threshold")]
+    pub threshold: f32, // color difference with a neighbour that asks for more samples
+    #[serde(default = "default_max_samples")]
+    pub max_samples: usize, // budget of a refined pixel
+}
+
+impl Default for Adaptive {
+    fn default() -> Self {
+        Self {
+            threshold: default_threshold(),
+            max_samples: default_max_samples(),
+        }
+    }
+}
+
+// how the image is computed, independent of what is in the scene
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenderSettings {
+    #[serde(default = "default_samples")]
+    pub samples: usize, // per pixel
+    #[serde(default)]
+    pub sampler: PixelSampler,
+    #[serde(default)]
+    pub filter: Filter,
+    #[serde(default)]
+    pub filter_radius: Option<f32>, // in pixels, each filter has its own default
+    #[serde(default)]
+    pub adaptive: Option<Adaptive>, // replaces `samples` when set
+    #[serde(default)]
+    pub integrator: Integrator,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize, // bounces of the path tracer
+    // light of the environment map in the whitted integrator, a few shadow rays
+    // per hit, the path tracer always has it
+    #[serde(default)]
+    pub environment_lighting: bool,
+    #[serde(default)]
+    pub display: DisplaySettings, // exposure, tone mapping and gamma of the image
+    #[serde(default)]
+    pub aovs: Vec<Aov>, // buffers saved next to the image
+    #[serde(default)]
+    pub denoise: Option<Denoiser>, // filters the noise of the image when set
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            samples: default_samples(),
+            sampler: PixelSampler::default(),
+            filter: Filter::default(),
+            filter_radius: None,
+            adaptive: None,
+            integrator: Integrator::default(),
+            max_depth: default_max_depth(),
+            environment_lighting: false,
+            display: DisplaySettings::default(),
+            aovs: Vec::new(),
+            denoise: None,
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn filter_radius(&self) -> f32 {
+        self.filter_radius
+            .unwrap_or_else(|| self.filter.default_radius())
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
+
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::structures::registry::{Registry, tagged};
+use crate::{Bounds, Intersect, Point};
+
+// implement it to add your own primitives without touching the Shape enum
+pub trait CustomShape: Intersect + Debug + Send + Sync {
+    // name used in the registry and in the "type" tag of the scene files
+    fn name(&self) -> &str;
+
+    // box around the shape in local space, rays missing it are never tested
+    fn bounds(&self) -> Bounds;
+
+    // texture coordinates of a local point on the surface, both between 0 and 1
+    fn uv_at(&self, point: &Point) -> (f32, f32);
+
+    // written next to the "type" tag when the scene is saved
+    fn parameters(&self) -> Value {
+        Value::Null
+    }
+}
+
+fn registry() -> &'static Registry<dyn CustomShape> {
+    static REGISTRY: OnceLock<Registry<dyn CustomShape>> = OnceLock::new();
+
+    REGISTRY.get_or_init(Registry::new)
+}
+
+// make a custom shape usable from the scene files under `name`
+pub fn register_shape<F>(name: &str, factory: F)
+where
+    F: Fn(&Value) -> Result<Arc<dyn CustomShape>, String> + Send + Sync + 'static,
+{
+    registry().register(name, Box::new(factory));
+}
+
+// shortcut for shapes that can be read directly from their json parameters
+pub fn register_shape_type<T>(name: &str)
+where
+    T: CustomShape + DeserializeOwned + 'static,
+{
+    register_shape(name, |value| {
+        let shape: T = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        Ok(Arc::new(shape))
+    });
+}
+
+pub fn registered_shapes() -> Vec<String> {
+    registry().names()
+}
+
+// build a shape from its scene file json, { "type": "name", ...parameters }
+pub fn create_shape(value: &Value) -> Result<Arc<dyn CustomShape>, String> {
+    registry().create(value)
+}
+
+pub fn serialize_custom<S>(shape: &Arc<dyn CustomShape>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    tagged(shape.name(), shape.parameters()).serialize(serializer)
+}
+
+pub fn deserialize_custom<'de, D>(deserializer: D) -> Result<Arc<dyn CustomShape>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+
+    create_shape(&value).map_err(D::Error::custom)
+}
@@ -8,4 +8,5 @@ pub mod triangle;
 pub mod tube;
 pub mod plane;
 pub mod cone;
-pub mod cylinder;
\ No newline at end of file
+pub mod cylinder;
+pub mod custom;
\ No newline at end of file
@@ -1,5 +1,9 @@
 
-use crate::{Sphere, Ray, LocalIntersection, Intersect, Cube, Disk, Torus, Triangle, Tube, Plane, Cone, Cylinder};
+use std::sync::Arc;
+
+use crate::structures::shapes::custom::{deserialize_custom, serialize_custom};
+use crate::{Sphere, Ray, LocalIntersection, Intersect, Cube, Disk, Torus, Triangle, Tube, Plane, Cone, Cylinder, CustomShape};
+use crate::{Bounds, EPSILON, Point};
 
 use serde::{Deserialize, Serialize};
 
@@ -14,9 +18,15 @@ pub enum Shape {
     Plane(Plane),
     Cone(Cone),
     Cylinder(Cylinder),
+    #[serde(serialize_with = "serialize_custom", deserialize_with = "deserialize_custom")]
+    Custom(Arc<dyn CustomShape>),
 }
 
 impl Shape {
+    pub fn custom(shape: impl CustomShape + 'static) -> Shape {
+        Shape::Custom(Arc::new(shape))
+    }
+
     pub fn intersect(&self, ray: Ray) -> Option<LocalIntersection> {
         match self {
             Shape::Sphere(s) => s.intersect(ray),
@@ -28,6 +38,47 @@ impl Shape {
             Shape::Plane(s) => s.intersect(ray),
             Shape::Cone(s) => s.intersect(ray),
             Shape::Cylinder(s) => s.intersect(ray),
+            Shape::Custom(s) => {
+                if s.bounds().intersects(&ray) {
+                    s.intersect(ray)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    // box around the shape in local space, infinite for the planes
+    pub fn bounds(&self) -> Bounds {
+        let cube = |x: f32, y0: f32, y1: f32| {
+            Bounds::new(Point::new(-x, y0, -x), Point::new(x, y1, x))
+        };
+
+        match self {
+            Shape::Sphere(s) => cube(s.radius, -s.radius, s.radius),
+            Shape::Cube(s) => cube(s.size / 2., -s.size / 2., s.size / 2.),
+            Shape::Disk(s) => cube(s.radius, -EPSILON, EPSILON),
+            Shape::Torus(s) => {
+                let r = s.major_radius + s.minor_radius;
+                cube(r, -r, r)
+            }
+            Shape::Triangle(s) => Bounds::new(
+                Point::new(
+                    s.p1.x.min(s.p2.x).min(s.p3.x),
+                    s.p1.y.min(s.p2.y).min(s.p3.y),
+                    s.p1.z.min(s.p2.z).min(s.p3.z),
+                ),
+                Point::new(
+                    s.p1.x.max(s.p2.x).max(s.p3.x),
+                    s.p1.y.max(s.p2.y).max(s.p3.y),
+                    s.p1.z.max(s.p2.z).max(s.p3.z),
+                ),
+            ),
+            Shape::Tube(s) => cube(s.radius, -s.height / 2., s.height / 2.),
+            Shape::Cylinder(s) => cube(s.radius, -s.height / 2., s.height / 2.),
+            Shape::Cone(s) => cube(s.radius, 0., s.height),
+            Shape::Plane(_) => Bounds::infinite(),
+            Shape::Custom(s) => s.bounds(),
         }
     }
 }
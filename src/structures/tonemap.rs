@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Color;
+
+// how the unbounded light of the film is squeezed into [0, 1]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapping {
+    #[default]
+    Clamp, // everything above 1 is white
+    Reinhard, // c / (1 + c), keeps the details of the highlights
+    Aces,     // filmic curve, more contrast and a soft shoulder
+}
+
+impl ToneMapping {
+    pub fn map(&self, c: f32) -> f32 {
+        let c = c.max(0.);
+
+        match self {
+            ToneMapping::Clamp => c.min(1.),
+            ToneMapping::Reinhard => c / (1. + c),
+            // fit of the aces curve by Krzysztof Narkowicz
+            ToneMapping::Aces => {
+                ((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14)).clamp(0., 1.)
+            }
+        }
+    }
+}
+
+impl FromStr for ToneMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "clamp" | "none" => Ok(ToneMapping::Clamp),
+            "reinhard" => Ok(ToneMapping::Reinhard),
+            "aces" | "filmic" => Ok(ToneMapping::Aces),
+            _ => Err(format!(
+                "unknown tone mapping '{}', expected clamp, reinhard or aces",
+                s
+            )),
+        }
+    }
+}
+
+fn default_srgb() -> bool {
+    false
+}
+
+// from the linear colors of the canvas to the bytes of the window and the files
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DisplaySettings {
+    #[serde(default)]
+    pub exposure: f32, // in stops, +1 doubles the light
+    #[serde(default)]
+    pub tone_mapping: ToneMapping,
+    #[serde(default = "default_srgb")]
+    pub srgb: bool, // opt-in, false writes the linear values as they are
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            exposure: 0.,
+            tone_mapping: ToneMapping::default(),
+            srgb: default_srgb(),
+        }
+    }
+}
+
+impl DisplaySettings {
+    // exposed and tone mapped, still linear, in [0, 1]
+    pub fn map(&self, color: Color) -> Color {
+        let scale = self.exposure.exp2();
+        let map = |c: f32| self.tone_mapping.map(c * scale);
+
+        Color::new(map(color.r), map(color.g), map(color.b))
+    }
+
+    pub fn encode(&self, color: Color) -> [u8; 3] {
+        let color = self.map(color);
+        let encode = |c: f32| {
+            // the linear values are truncated as they always were
+            if self.srgb {
+                (linear_to_srgb(c) * 255. + 0.5) as u8
+            } else {
+                (c * 255.) as u8
+            }
+        };
+
+        [encode(color.r), encode(color.g), encode(color.b)]
+    }
+
+    // 0RGB, the pixel format of the window
+    pub fn pack(&self, color: Color) -> u32 {
+        let [r, g, b] = self.encode(color);
+
+        ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+    }
+}
+
+// the srgb transfer curve, linear near black then a 2.4 power
+pub fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.clamp(0., 1.);
+
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    }
+}
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    let c = c.clamp(0., 1.);
+
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
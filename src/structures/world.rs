@@ -1,10 +1,33 @@
 
+use std::f32::consts::PI;
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
-use crate::{Object, Light, Ray, Intersection, Point, Vector, Color};
+use crate::EPSILON;
+use crate::structures::brdf::{Bsdf, Ggx};
+use crate::structures::sampler::{Rng, power_heuristic};
+use crate::{Object, Light, Ray, Intersection, Point, Vector, Color, Ambient, Background};
+
+// transparent surfaces a shadow ray can go through before giving up
+const MAX_SHADOW_HITS: usize = 16;
+
+// how the contributions of several lights are added
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum LightModel {
+    Physical, // attenuated by the distance and added
+    #[default]
+    Legacy,   // no attenuation, averaged over the lights
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct World {
+    #[serde(default)]
+    pub light_model: LightModel,
+    #[serde(default)]
+    pub ambient: Ambient,
+    #[serde(default)]
+    pub background: Background,
     pub objects: Vec<Object>,
     pub lights: Vec<Light>,
 }
@@ -12,6 +35,9 @@ pub struct World {
 impl World {
     pub fn new() -> World {
         World {
+            light_model: LightModel::default(),
+            ambient: Ambient::default(),
+            background: Background::default(),
             lights: Vec::new(),
             objects: Vec::new(),
         }
@@ -25,6 +51,27 @@ impl World {
         self.lights.push(light);
     }
 
+    // the scene as it is at a time of the animation in seconds, the objects that
+    // move are blurred over the duration of a frame
+    pub fn at_time(&self, time: f32, frame_duration: f32) -> World {
+        let mut world = self.clone();
+
+        for object in &mut world.objects {
+            object.animate(time, frame_duration);
+            object.update();
+        }
+        for light in &mut world.lights {
+            light.animate(time);
+        }
+
+        world
+    }
+
+    // files the scene points to (environment maps), relative to `base`
+    pub fn load_resources(&mut self, base: Option<&Path>) -> Result<(), String> {
+        self.background.load(base)
+    }
+
     pub fn intersect(&self, ray: &Ray, n1: f32) -> Option<Intersection> {
         let mut closest_intersection = None;
 
@@ -46,6 +93,96 @@ impl World {
         closest_intersection
     }
 
+    // light left after crossing the transparent objects on the way, tinted by their color
+    pub fn transmittance(&self, point: &Point, direction: &Vector, distance: f32) -> Color {
+        self.transmittance_at(point, direction, distance, 0.)
+    }
+
+    // the same, with the moving objects where they are at that time of the shutter
+    pub fn transmittance_at(
+        &self,
+        point: &Point,
+        direction: &Vector,
+        distance: f32,
+        time: f32,
+    ) -> Color {
+        let mut tint = Color::WHITE;
+        let mut origin = *point;
+        let mut remaining = distance;
+
+        for _ in 0..MAX_SHADOW_HITS {
+            let r = Ray::new(origin, *direction).at_time(time);
+
+            let h = match self.intersect(&r, 1.) {
+                Some(h) if h.t < remaining => h,
+                _ => return tint,
+            };
+
+            let material = &h.object.material;
+            if material.transparency <= 0. {
+                return Color::BLACK;
+            }
+
+            tint *= h.object.color_at(&h.point) * material.transparency;
+            if tint.is_black() {
+                return tint;
+            }
+
+            // continue just behind the surface
+            origin = h.point + *direction * EPSILON;
+            remaining -= h.t + EPSILON;
+        }
+
+        tint
+    }
+
+    // light of the environment map reaching the point and reflected toward the eye
+    // half the samples follow the bright parts of the map, half the material
+    pub fn image_based_lighting(
+        &self,
+        obj: &Object,
+        point: &Point,
+        eyev: &Vector,
+        normalv: &Vector,
+        time: f32,
+    ) -> Color {
+        let map = match &self.background {
+            Background::Environment(map) if map.lighting && map.is_loaded() => map,
+            _ => return Color::BLACK,
+        };
+
+        let brdf = Bsdf::from_object(obj, point);
+        let mut rng = Rng::from_point(point);
+        let samples = map.samples.max(1);
+        let mut color = Color::BLACK;
+
+        for _ in 0..samples {
+            if let Some((wi, radiance, pdf)) = map.sample(rng.next_f32(), rng.next_f32()) {
+                let f = brdf.eval(&wi, eyev, normalv);
+
+                if !f.is_black() {
+                    let weight = power_heuristic(pdf, brdf.pdf(&wi, eyev, normalv));
+                    let visibility = self.transmittance_at(point, &wi, f32::INFINITY, time);
+                    color += radiance * f * visibility * (wi.dot(normalv) * weight / pdf);
+                }
+            }
+
+            let (u, v, w) = (rng.next_f32(), rng.next_f32(), rng.next_f32());
+            if let Some(wi) = brdf.sample(eyev, normalv, u, v, w) {
+                let pdf = brdf.pdf(&wi, eyev, normalv);
+                let f = brdf.eval(&wi, eyev, normalv);
+
+                if pdf > 0. && !f.is_black() {
+                    let weight = power_heuristic(pdf, map.pdf(&wi));
+                    let visibility = self.transmittance_at(point, &wi, f32::INFINITY, time);
+                    color += map.radiance(&wi) * f * visibility * (wi.dot(normalv) * weight / pdf);
+                }
+            }
+        }
+
+        color * (1. / samples as f32)
+    }
+
     // shadow and light
     pub fn lighting(
         obj: &Object,
@@ -55,39 +192,82 @@ impl World {
         normalv: &Vector,
         shadowed: bool,
     ) -> Color {
-        let effective_color;
-        if obj.material.pattern.is_some() {
-            effective_color = obj
-                .material
-                .pattern
-                .clone()
-                .unwrap()
-                .stripe_at_object(obj, point);
-        } else {
-            effective_color = obj.material.color * light.intensity;
-        }
+        let visibility = if shadowed { Color::BLACK } else { Color::WHITE };
+
+        World::lighting_with_visibility(obj, light, point, eyev, normalv, visibility)
+    }
+
+    // visibility is the light that is not blocked, tinted by transparent objects and
+    // partial for soft shadows (white when nothing is in the way)
+    pub fn lighting_with_visibility(
+        obj: &Object,
+        light: &Light,
+        point: &Point,
+        eyev: &Vector,
+        normalv: &Vector,
+        visibility: Color,
+    ) -> Color {
+        let ambient = World::effective_color(obj, light, point) * obj.material.ambient;
+
+        ambient + World::reflected_light(obj, light, point, eyev, normalv, visibility)
+    }
+
+    // diffuse and specular light of one light, without the ambient
+    pub fn reflected_light(
+        obj: &Object,
+        light: &Light,
+        point: &Point,
+        eyev: &Vector,
+        normalv: &Vector,
+        visibility: Color,
+    ) -> Color {
+        let effective_color = World::effective_color(obj, light, point);
 
-        let lightv = (light.position - *point).normalize();
+        let lightv = light.direction_from(point);
+        let falloff = light.falloff(point);
 
-        let ambient = effective_color * obj.material.ambient;
         let light_dot_normal = lightv.dot(normalv);
 
-        if light_dot_normal < 0. || shadowed == true {
-            return ambient;
+        if light_dot_normal < 0. || visibility.is_black() || falloff <= 0. {
+            return Color::BLACK;
         }
 
-        let diffuse = effective_color * obj.material.diffuse * light_dot_normal;
+        if let Some(pbr) = &obj.material.pbr {
+            // same scale as the phong shading: times pi, a white diffuse surface is as bright
+            let brdf = Ggx::new(obj.color_at(point), pbr);
+            let reflected = brdf.eval(&lightv, eyev, normalv) * light.intensity;
+
+            return reflected * visibility * (light_dot_normal * falloff * PI);
+        }
+
+        let diffuse =
+            effective_color * obj.material.diffuse * light_dot_normal * falloff * visibility;
 
         let reflectv = (-lightv).reflect(normalv);
         let reflect_dot_eye = reflectv.dot(eyev);
 
         if reflect_dot_eye <= 0. {
-            return ambient + diffuse;
+            diffuse
         } else {
             let factor = reflect_dot_eye.powf(obj.material.shininess);
-            let specular = light.intensity * obj.material.specular * factor;
-            return ambient + diffuse + specular;
+            let specular =
+                light.intensity * obj.material.specular * factor * falloff * visibility;
+            diffuse + specular
         }
     }
 
+    // the patterns keep their own colors whatever the light, like in the first maps
+    fn effective_color(obj: &Object, light: &Light, point: &Point) -> Color {
+        match &obj.material.pattern {
+            Some(_) => obj.color_at(point),
+            None => obj.material.color * light.intensity,
+        }
+    }
+
+    // ambient light of the world reflected by the surface, once for all the lights
+    // and whatever their distance (physical light model)
+    pub fn ambient_at(&self, obj: &Object, point: &Point) -> Color {
+        self.ambient.light() * obj.color_at(point)
+    }
+
 }
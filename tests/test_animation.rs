@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Color, Frames, Interpolation, Keyframe, Light, NewCamera, Object, ObjectAnimation, Options,
+        Point, Shape, Sphere, Vector, World, frame_file_name, get_info_map, sort_keyframes,
+        value_at,
+    };
+
+    #[test]
+    fn test_linear_keyframes() {
+        let keys = vec![Keyframe::new(1., 0.), Keyframe::new(3., 10.)];
+
+        assert_eq!(value_at(&keys, 2.), Some(5.));
+        assert_eq!(value_at(&keys, 2.5), Some(7.5));
+
+        // held before the first and after the last
+        assert_eq!(value_at(&keys, 0.), Some(0.));
+        assert_eq!(value_at(&keys, 9.), Some(10.));
+
+        let empty: Vec<Keyframe<f32>> = Vec::new();
+        assert_eq!(value_at(&empty, 1.), None);
+    }
+
+    #[test]
+    fn test_step_and_bezier_keyframes() {
+        let keys = vec![
+            Keyframe::new(0., 0.).interpolation(Interpolation::Step),
+            Keyframe::new(1., 1.).interpolation(Interpolation::ease()),
+            Keyframe::new(2., 2.),
+        ];
+
+        assert_eq!(value_at(&keys, 0.99), Some(0.));
+        assert_eq!(value_at(&keys, 1.), Some(1.));
+
+        // slow at both ends, symmetric around the middle
+        let ease = Interpolation::ease();
+        assert!(ease.progress(0.1) < 0.1);
+        assert!(ease.progress(0.9) > 0.9);
+        assert!((ease.progress(0.5) - 0.5).abs() < 1e-4);
+        assert!((value_at(&keys, 1.5).unwrap() - 1.5).abs() < 1e-4);
+        assert_eq!(ease.progress(0.), 0.);
+        assert!((ease.progress(1.) - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_animated_object() {
+        let animation = ObjectAnimation {
+            position: vec![
+                Keyframe::new(0., Point::new(0., 0., 0.)),
+                Keyframe::new(1., Point::new(4., 0., 0.)),
+            ],
+            color: vec![
+                Keyframe::new(0., Color::new(1., 0., 0.)),
+                Keyframe::new(1., Color::new(0., 0., 1.)),
+            ],
+            ..ObjectAnimation::default()
+        };
+        let mut world = World::new();
+        world.add_object(Object::new(Shape::Sphere(Sphere::new(1.))).animation(animation));
+
+        let scene = world.at_time(0.5, 0.25);
+        let ball = &scene.objects[0];
+        assert_eq!(ball.position, Point::new(2., 0., 0.));
+        assert_eq!(ball.material.color.blue(), 0.5);
+        assert_eq!(
+            ball.local_to_world.clone() * Point::new(0., 0., 0.),
+            ball.position
+        );
+
+        // blurred toward where it is a frame later
+        let motion = ball.motion.clone().unwrap();
+        assert_eq!(motion.position, Some(Point::new(3., 0., 0.)));
+        assert_eq!(motion.yaw, None);
+
+        // the original scene doesn't change
+        assert_eq!(world.objects[0].position, Point::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_animation_from_json() {
+        let light: Light = serde_json::from_str(
+            r#"{
+                "intensity": { "r": 1.0, "g": 1.0, "b": 1.0 },
+                "animation": {
+                    "intensity": [
+                        { "time": 0.0, "value": { "r": 0.0, "g": 0.0, "b": 0.0 } },
+                        { "time": 2.0, "value": { "r": 1.0, "g": 1.0, "b": 1.0 },
+                          "interpolation": { "Bezier": [0.0, 0.0, 1.0, 1.0] } }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut world = World::new();
+        world.add_light(light);
+        assert_eq!(world.at_time(1., 0.).lights[0].intensity.red(), 0.5);
+
+        let camera: NewCamera = serde_json::from_str(
+            r#"{
+                "fov": 60.0,
+                "animation": {
+                    "position": [
+                        { "time": 0.0, "value": { "x": 0.0, "y": 0.0, "z": 10.0 } },
+                        { "time": 1.0, "value": { "x": 0.0, "y": 0.0, "z": 5.0 } }
+                    ],
+                    "direction": [
+                        { "time": 0.0, "value": { "x": 0.0, "y": 0.0, "z": -1.0 },
+                          "interpolation": "Step" }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+        let camera = camera.at_time(0.4);
+        assert_eq!(camera.position, Point::new(0., 0., 8.));
+        assert_eq!(camera.direction, Vector::new(0., 0., -1.));
+        assert_eq!(camera.fov, 60.);
+    }
+
+    #[test]
+    fn test_keyframes_out_of_order() {
+        let mut keys = vec![
+            Keyframe::new(3., 10.),
+            Keyframe::new(1., 0.),
+            Keyframe::new(3., 20.),
+        ];
+        sort_keyframes(&mut keys);
+        assert_eq!(value_at(&keys, 2.), Some(5.));
+        // the last one written wins at the same time
+        assert_eq!(value_at(&keys, 3.), Some(20.));
+
+        let file = std::env::temp_dir().join("new_rt_unsorted_keyframes.json");
+        std::fs::write(
+            &file,
+            r#"{
+                "canvas": { "width": 1, "height": 1 },
+                "world": { "objects": [], "lights": [] },
+                "camera": {
+                    "animation": {
+                        "fov": [
+                            { "time": 2.0, "value": 60.0 },
+                            { "time": 0.0, "value": 40.0 }
+                        ]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let map = get_info_map(&file.to_str().unwrap().to_string()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(map.camera.at_time(1.).fov, 50.);
+    }
+
+    #[test]
+    fn test_map_without_fps() {
+        let file = std::env::temp_dir().join("new_rt_zero_fps.json");
+        std::fs::write(
+            &file,
+            r#"{
+                "canvas": { "width": 1, "height": 1 },
+                "world": { "objects": [], "lights": [] },
+                "camera": {},
+                "frames": { "start": 0, "end": 10, "fps": 0.0 }
+            }"#,
+        )
+        .unwrap();
+        let map = get_info_map(&file.to_str().unwrap().to_string());
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(map.unwrap_err().to_string(), "the fps must be more than 0");
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn test_unsorted_keyframes_are_caught() {
+        let keys = vec![Keyframe::new(3., 10.), Keyframe::new(1., 0.)];
+        value_at(&keys, 2.);
+    }
+
+    #[test]
+    fn test_frames() {
+        let frames = Frames::new(10, 33, 24.);
+        assert_eq!(frames.len(), 24);
+        assert_eq!(frames.time(12), 0.5);
+        assert_eq!(frames.duration(), 1. / 24.);
+
+        let frames: Frames = serde_json::from_str("{}").unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames.fps, 24.);
+
+        let args: Vec<String> = ["a.json", "--frames", "5-8", "--fps", "30"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut frames = Frames::default();
+        Options::parse(&args).unwrap().apply_frames(&mut frames);
+        assert_eq!(frames, Frames::new(5, 8, 30.));
+
+        let bad = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            Options::parse(&args).is_err()
+        };
+        assert!(bad(&["a.json", "--frames", "8-5"]));
+        assert!(bad(&["a.json", "--frames", "a-b"]));
+        assert!(bad(&["a.json", "--fps", "0"]));
+        assert!(!bad(&["a.json", "--frames", "7"]));
+    }
+
+    #[test]
+    fn test_frame_file_names() {
+        assert_eq!(frame_file_name("shot.png", 7), "shot_0007.png");
+        assert_eq!(frame_file_name("out/shot_###.ppm", 42), "out/shot_042.ppm");
+        assert_eq!(frame_file_name("f#.png", 123), "f123.png");
+        assert_eq!(frame_file_name("out.d/shot", 1), "out.d/shot_0001");
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Adaptive, Aov, Camera, Canvas, Color, DisplaySettings, Light, Material, NewCamera,
+        NewCanvas, Object, Options, Point, Renderer, Shape, Sphere, World, pass_file_name,
+    };
+
+    fn renderer(aovs: Vec<Aov>) -> Renderer {
+        let mut world = World::new();
+        world.add_object(Object::new(Shape::Sphere(Sphere::new(1.))));
+        // on the lit side of the first ball, big enough for a pixel to show it
+        let mut mirror =
+            Object::new(Shape::Sphere(Sphere::new(2.))).material(Material::new().reflective(0.5));
+        mirror.position = Point::new(-3.5, 0., 0.);
+        world.add_object(mirror);
+        world.add_light(Light::new(Point::new(-10., 10., 10.), Color::WHITE));
+
+        let canvas = Canvas::new(NewCanvas {
+            width: 9,
+            height: 9,
+        });
+        let mut renderer = Renderer::headless(canvas, world);
+        renderer.camera = Camera::from_settings(&NewCamera::default(), 1.);
+        renderer.settings.aovs = aovs;
+
+        renderer
+    }
+
+    fn pass(renderer: &Renderer, aov: Aov) -> &Canvas {
+        &renderer.passes.iter().find(|(a, _)| *a == aov).unwrap().1
+    }
+
+    #[test]
+    fn test_data_passes() {
+        let mut renderer = renderer(vec![Aov::Depth, Aov::Normal, Aov::ObjectId, Aov::Albedo]);
+        renderer.update_image();
+        renderer.render_passes();
+        assert_eq!(renderer.passes.len(), 4);
+
+        // the center of the first ball, 9 units away, facing the camera
+        let depth = pass(&renderer, Aov::Depth).at(4, 4).red();
+        assert!((depth - 9.).abs() < 1e-3);
+        let normal = pass(&renderer, Aov::Normal).at(4, 4);
+        assert!((normal.blue() - 1.).abs() < 1e-3);
+        assert_eq!(pass(&renderer, Aov::ObjectId).at(4, 4).red(), 1.);
+        assert_eq!(pass(&renderer, Aov::Albedo).at(4, 4).red(), 1.);
+
+        // nothing in the corner
+        assert_eq!(pass(&renderer, Aov::Depth).at(0, 0).red(), f32::INFINITY);
+        assert_eq!(pass(&renderer, Aov::ObjectId).at(0, 0).red(), 0.);
+    }
+
+    #[test]
+    fn test_light_passes_add_up_to_the_image() {
+        let mut renderer = renderer(vec![Aov::Direct, Aov::Reflected]);
+        renderer.update_image();
+        renderer.render_passes();
+
+        let mut bounced = false;
+        for y in 0..9 {
+            for x in 0..9 {
+                let sum = pass(&renderer, Aov::Direct).at(x, y)
+                    + pass(&renderer, Aov::Reflected).at(x, y);
+                let image = renderer.canvas.at(x, y);
+                assert!((sum - image).luminance().abs() < 1e-4);
+                bounced |= !pass(&renderer, Aov::Reflected).at(x, y).is_black();
+            }
+        }
+        // the mirror ball shows the other one
+        assert!(bounced);
+    }
+
+    #[test]
+    fn test_light_passes_of_the_refined_pixels() {
+        // the same samples when some pixels get more
+        let mut renderer = renderer(vec![Aov::Direct, Aov::Reflected]);
+        renderer.settings.adaptive = Some(Adaptive::default());
+        renderer.update_image();
+        renderer.render_passes();
+
+        for y in 0..9 {
+            for x in 0..9 {
+                let sum = pass(&renderer, Aov::Direct).at(x, y)
+                    + pass(&renderer, Aov::Reflected).at(x, y);
+                assert!((sum - renderer.canvas.at(x, y)).luminance().abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pass_files() {
+        assert_eq!(
+            pass_file_name("shot_0001.png", Aov::Depth),
+            "shot_0001_depth.png"
+        );
+        assert_eq!(pass_file_name("out.d/shot", Aov::ObjectId), "out.d/shot_id");
+
+        let mut renderer = renderer(vec![Aov::Depth]);
+        renderer.update_image();
+        renderer.render_passes();
+        let depth = pass(&renderer, Aov::Depth);
+        let display = DisplaySettings::default();
+
+        // the exr keeps the distances, the png shows them
+        let dir = std::env::temp_dir();
+        let exr = dir.join("new_rt_depth.exr");
+        let exr = exr.to_str().unwrap();
+        Aov::Depth.save(depth, exr, &display).unwrap();
+        let picture = image::open(exr).unwrap().to_rgb32f();
+        std::fs::remove_file(exr).unwrap();
+        assert!((picture.get_pixel(4, 4)[0] - 9.).abs() < 1e-3);
+
+        let png = dir.join("new_rt_depth.png");
+        let png = png.to_str().unwrap();
+        Aov::Depth.save(depth, png, &display).unwrap();
+        let picture = image::open(png).unwrap().to_rgb8();
+        std::fs::remove_file(png).unwrap();
+        assert_eq!(picture.get_pixel(0, 0)[0], 0);
+        assert!(picture.get_pixel(4, 4)[0] > 0);
+
+        // the albedo keeps the colors of the materials, without the srgb curve
+        let mut albedo = Canvas::new(NewCanvas {
+            width: 1,
+            height: 1,
+        });
+        albedo.write(0, 0, Color::new(0.2, 0.2, 0.2));
+        let png = dir.join("new_rt_albedo.png");
+        let png = png.to_str().unwrap();
+        Aov::Albedo.save(&albedo, png, &display).unwrap();
+        let picture = image::open(png).unwrap().to_rgb8();
+        std::fs::remove_file(png).unwrap();
+        assert_eq!(picture.get_pixel(0, 0)[0], 51);
+    }
+
+    #[test]
+    fn test_passes_from_scene_and_command_line() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+
+        let options = Options::parse(&args(&[
+            "a.json",
+            "--aov",
+            "depth,normal,depth",
+            "--output",
+            "a.exr",
+        ]))
+        .unwrap();
+        assert_eq!(options.aovs, Some(vec![Aov::Depth, Aov::Normal]));
+
+        let mut settings = serde_json::from_str(r#"{ "aovs": ["Albedo"] }"#).unwrap();
+        options.apply(&mut settings);
+        assert_eq!(settings.aovs, vec![Aov::Depth, Aov::Normal]);
+
+        assert!(Options::parse(&args(&["a.json", "--aov", "depth"])).is_err());
+        assert!(
+            Options::parse(&args(&["a.json", "--aov", "beauty", "--output", "a.png"])).is_err()
+        );
+    }
+}
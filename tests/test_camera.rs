@@ -0,0 +1,301 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Camera, Canvas, Color, Emission, Eye, Material, NewCamera, NewCanvas, Object, Options,
+        Point, Projection, Ray, Renderer, Shape, Sphere, Stereo, StereoLayout, Vector, World,
+    };
+
+    fn camera() -> Camera {
+        Camera::new(
+            Point::new(0., 0., 10.),
+            Vector::new(0., 0., -1.),
+            1.,
+            45f32.to_radians(),
+            0.1,
+            100.,
+        )
+    }
+
+    #[test]
+    fn test_pinhole_camera_keeps_the_ray() {
+        let camera = camera();
+        let pinhole = Ray::new(Point::new(0., 0., 9.9), Vector::new(0.1, 0., -1.));
+
+        let ray = camera.lens_ray(&pinhole, 0.9, 0.1);
+        assert_eq!(ray.origin, pinhole.origin);
+        assert_eq!(ray.direction, pinhole.direction);
+    }
+
+    #[test]
+    fn test_lens_rays_meet_on_the_focus_plane() {
+        let camera = camera().aperture(0.5).focus_distance(4.);
+        let pinhole = Ray::new(
+            Point::new(0., 0., 10.),
+            Vector::new(0.2, -0.1, -1.).normalize(),
+        );
+
+        let mut origins = Vec::new();
+        for (u, v) in [(0.1, 0.1), (0.9, 0.2), (0.5, 0.95), (0.3, 0.6)] {
+            let ray = camera.lens_ray(&pinhole, u, v);
+
+            // on the lens, around the camera position
+            assert!((ray.origin - camera.position).magnitude() <= 0.5 + 1e-4);
+            assert_eq!(ray.origin.z, 10.);
+            origins.push(ray.origin);
+
+            // every ray crosses the plane z = 6 at the same point as the pinhole ray
+            let t = (6. - ray.origin.z) / ray.direction.z;
+            let p = ray.position(t);
+            assert!((p.x - 0.8).abs() < 1e-4);
+            assert!((p.y + 0.4).abs() < 1e-4);
+        }
+
+        assert!(origins[0] != origins[1]);
+    }
+
+    #[test]
+    fn test_focus_on_a_point() {
+        let mut camera = camera();
+
+        camera.focus_on(&Point::new(3., 1., 2.));
+        assert_eq!(camera.focus_distance, 8.);
+
+        // behind the camera, nothing changes
+        camera.focus_on(&Point::new(0., 0., 20.));
+        assert_eq!(camera.focus_distance, 8.);
+    }
+
+    #[test]
+    fn test_camera_from_scene_file() {
+        let settings: NewCamera = serde_json::from_str("{}").unwrap();
+        let camera = Camera::from_settings(&settings, 2.);
+        assert_eq!(camera.position, Point::new(0., 0., 10.));
+        assert_eq!(camera.aperture, 0.);
+        assert!(!camera.autofocus);
+
+        let settings: NewCamera = serde_json::from_str(
+            r#"{
+                "position": { "x": 0.0, "y": 1.0, "z": 5.0 },
+                "fov": 30.0,
+                "aperture": 0.2,
+                "focus_distance": 5.0,
+                "autofocus": true
+            }"#,
+        )
+        .unwrap();
+        let camera = Camera::from_settings(&settings, 2.);
+        assert_eq!(camera.position, Point::new(0., 1., 5.));
+        assert_eq!(camera.fov, 30f32.to_radians());
+        assert_eq!(camera.aperture, 0.2);
+        assert_eq!(camera.focus_distance, 5.);
+        assert!(camera.autofocus);
+    }
+
+    #[test]
+    fn test_shutter() {
+        let camera = camera();
+        assert_eq!(camera.shutter_time(0.6), 0.);
+
+        let camera = camera.shutter(0.25, 0.75);
+        assert_eq!(camera.shutter_time(0.), 0.25);
+        assert_eq!(camera.shutter_time(0.5), 0.5);
+
+        // closes after it opens, inside the frame
+        let camera = camera.shutter(0.5, 0.2);
+        assert_eq!(camera.shutter_close, 0.5);
+
+        let settings: NewCamera =
+            serde_json::from_str(r#"{ "shutter_open": 0.0, "shutter_close": 0.5 }"#).unwrap();
+        let camera = Camera::from_settings(&settings, 1.);
+        assert_eq!(camera.shutter_close, 0.5);
+    }
+
+    fn wide() -> Camera {
+        let mut camera = camera();
+        camera.resize(2.);
+
+        camera
+    }
+
+    fn assert_direction(ray: &Ray, x: f32, y: f32, z: f32) {
+        let expected = Vector::new(x, y, z).normalize();
+        assert!((ray.direction - expected).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn test_perspective_rays() {
+        let camera = wide();
+
+        let center = camera.ray(0.5, 0.5).unwrap();
+        assert_eq!(center.origin, camera.position);
+        assert_direction(&center, 0., 0., -1.);
+
+        // the top of the image looks up, the right side looks right
+        let tan = (22.5f32).to_radians().tan();
+        assert_direction(&camera.ray(0.5, 0.).unwrap(), 0., tan, -1.);
+        assert_direction(&camera.ray(1., 0.5).unwrap(), 2. * tan, 0., -1.);
+    }
+
+    #[test]
+    fn test_orthographic_rays_are_parallel() {
+        let camera = wide().projection(Projection::Orthographic).view_height(4.);
+
+        let corner = camera.ray(0., 0.).unwrap();
+        assert_direction(&corner, 0., 0., -1.);
+        assert!((corner.origin - Point::new(-4., 2., 10.)).magnitude() < 1e-4);
+
+        let center = camera.ray(0.5, 0.5).unwrap();
+        assert_eq!(center.origin, camera.position);
+        assert_direction(&center, 0., 0., -1.);
+    }
+
+    #[test]
+    fn test_fisheye_rays() {
+        let mut camera = camera().projection(Projection::Fisheye);
+        camera.fov = 180f32.to_radians();
+
+        assert_direction(&camera.ray(0.5, 0.5).unwrap(), 0., 0., -1.);
+        // the edge of the circle is 90 degrees away from the center
+        assert_direction(&camera.ray(1., 0.5).unwrap(), 1., 0., 0.);
+        assert_direction(&camera.ray(0.5, 0.).unwrap(), 0., 1., 0.);
+
+        // nothing in the corners
+        assert!(camera.ray(0., 0.).is_none());
+    }
+
+    #[test]
+    fn test_equirectangular_rays() {
+        let camera = camera().projection(Projection::Equirectangular);
+
+        assert_direction(&camera.ray(0.5, 0.5).unwrap(), 0., 0., -1.);
+        assert_direction(&camera.ray(0.75, 0.5).unwrap(), 1., 0., 0.);
+        assert_direction(&camera.ray(0.25, 0.5).unwrap(), -1., 0., 0.);
+        assert_direction(&camera.ray(0., 0.5).unwrap(), 0., 0., 1.);
+        assert_direction(&camera.ray(0.5, 0.).unwrap(), 0., 1., 0.);
+        assert_direction(&camera.ray(0.3, 1.).unwrap(), 0., -1., 0.);
+    }
+
+    #[test]
+    fn test_projection_from_scene_and_command_line() {
+        let settings: NewCamera =
+            serde_json::from_str(r#"{ "projection": "Orthographic", "view_height": 3.0 }"#)
+                .unwrap();
+        let camera = Camera::from_settings(&settings, 1.);
+        assert_eq!(camera.projection, Projection::Orthographic);
+        assert_eq!(camera.view_height, 3.);
+
+        let args: Vec<String> = ["a.json", "--projection", "360", "--output", "a.png"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = Options::parse(&args).unwrap();
+        let mut settings = NewCamera::default();
+        options.apply_camera(&mut settings);
+        assert_eq!(settings.projection, Projection::Equirectangular);
+        assert_eq!(options.output.as_deref(), Some("a.png"));
+
+        assert!("pinhole".parse::<Projection>().is_err());
+    }
+
+    #[test]
+    fn test_headless_render() {
+        let mut world = World::new();
+        let ball = Object::new(Shape::Sphere(Sphere::new(8.)))
+            .material(Material::new().emission(Emission::new(Color::new(1., 0., 0.), 1.)));
+        world.add_object(ball);
+
+        let canvas = Canvas::new(NewCanvas {
+            width: 8,
+            height: 4,
+        });
+        let mut renderer = Renderer::headless(canvas, world);
+        renderer.camera = Camera::from_settings(&NewCamera::default(), 2.)
+            .projection(Projection::Equirectangular);
+        renderer.update_image();
+
+        // the ball is in front of the camera, in the middle of the panorama
+        assert!(renderer.canvas.at(4, 2).red() > 0.);
+        assert!(renderer.canvas.at(0, 2).red() == 0.);
+        assert!(renderer.render().is_ok());
+    }
+
+    #[test]
+    fn test_stereo_eyes_converge() {
+        let stereo = Stereo::new(StereoLayout::SideBySide)
+            .eye_separation(0.2)
+            .convergence(5.);
+        let camera = wide().stereo(Some(stereo));
+
+        let left = camera.eye_ray(Eye::Left, 0.7, 0.4).unwrap();
+        let right = camera.eye_ray(Eye::Right, 0.7, 0.4).unwrap();
+        assert!((left.origin - Point::new(-0.1, 0., 10.)).magnitude() < 1e-5);
+        assert!((right.origin - Point::new(0.1, 0., 10.)).magnitude() < 1e-5);
+
+        // both cross the plane at the convergence distance at the same point
+        let meet = |ray: &Ray| ray.position((5. - ray.origin.z) / ray.direction.z);
+        assert!((meet(&left) - meet(&right)).magnitude() < 1e-4);
+
+        // each eye has a square half of the image
+        let center = camera.view_ray(0.5, 0.5).unwrap();
+        assert_direction(&center, 0., 0., -1.);
+        let tan = (22.5f32).to_radians().tan();
+        assert_direction(&camera.view_ray(1., 0.5).unwrap(), tan, 0., -1.);
+    }
+
+    #[test]
+    fn test_stereo_layouts() {
+        let side_by_side = wide().stereo(Some(Stereo::default()));
+        assert!(side_by_side.ray(0.25, 0.5).unwrap().origin.x < 0.);
+        assert!(side_by_side.ray(0.75, 0.5).unwrap().origin.x > 0.);
+
+        // the same point of each half
+        let left = side_by_side.ray(0.1, 0.3).unwrap();
+        let expected = side_by_side.eye_ray(Eye::Left, 0.2, 0.3).unwrap();
+        assert_eq!(left.origin, expected.origin);
+        assert_eq!(left.direction, expected.direction);
+
+        let top_bottom = wide().stereo(Some(Stereo::new(StereoLayout::TopBottom)));
+        assert!(top_bottom.ray(0.5, 0.25).unwrap().origin.x < 0.);
+        assert!(top_bottom.ray(0.5, 0.75).unwrap().origin.x > 0.);
+    }
+
+    #[test]
+    fn test_omni_directional_stereo() {
+        let camera = wide()
+            .projection(Projection::Equirectangular)
+            .stereo(Some(Stereo::new(StereoLayout::TopBottom)));
+
+        // looking right, the left eye is in front of the camera
+        let left = camera.eye_ray(Eye::Left, 0.75, 0.5).unwrap();
+        assert!((left.origin - Point::new(0., 0., 9.968)).magnitude() < 1e-4);
+
+        // behind, it is on the right
+        let left = camera.eye_ray(Eye::Left, 0., 0.5).unwrap();
+        assert!((left.origin - Point::new(0.032, 0., 10.)).magnitude() < 1e-4);
+        assert!(left.direction.z > 0.99);
+    }
+
+    #[test]
+    fn test_stereo_from_scene_and_command_line() {
+        let settings: NewCamera = serde_json::from_str(r#"{ "stereo": {} }"#).unwrap();
+        let stereo = settings.stereo.unwrap();
+        assert_eq!(stereo.layout, StereoLayout::SideBySide);
+        assert_eq!(stereo.eye_separation, 0.064);
+        assert!(NewCamera::default().stereo.is_none());
+
+        let settings: NewCamera =
+            serde_json::from_str(r#"{ "stereo": { "layout": "TopBottom", "convergence": 3.0 } }"#)
+                .unwrap();
+        let mut camera = settings.clone();
+        let args: Vec<String> = ["a.json", "--stereo", "sbs"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        Options::parse(&args).unwrap().apply_camera(&mut camera);
+
+        // only the layout changes
+        let stereo = camera.stereo.unwrap();
+        assert_eq!(stereo.layout, StereoLayout::SideBySide);
+        assert_eq!(stereo.convergence, 3.);
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{Animatable, Camera, CameraPath, CameraPose, Options, Point, Vector};
+
+    fn camera() -> Camera {
+        Camera::new(
+            Point::new(0., 0., 10.),
+            Vector::new(0., 0., -1.),
+            1.,
+            45f32.to_radians(),
+            0.1,
+            100.,
+        )
+    }
+
+    #[test]
+    fn test_pose_of_the_camera() {
+        let mut camera = camera();
+        let pose = CameraPose::new(Point::new(1., 2., 3.), 10., 45.);
+
+        camera.set_pose(&pose);
+        assert_eq!(camera.pose(), pose);
+
+        // no smoothing toward the old position
+        camera.update();
+        assert_eq!(camera.position, Point::new(1., 2., 3.));
+    }
+
+    #[test]
+    fn test_poses_turn_the_short_way() {
+        let from = CameraPose::new(Point::new(0., 0., 0.), 0., 350.);
+        let to = CameraPose::new(Point::new(2., 0., 0.), 20., 10.);
+
+        let middle = from.lerp(to, 0.5);
+        assert_eq!(middle.position, Point::new(1., 0., 0.));
+        assert_eq!(middle.pitch, 10.);
+        assert_eq!(middle.yaw, 0.);
+        assert_eq!(from.lerp(to, 0.25).yaw, 355.);
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut camera = camera();
+        let mut path = CameraPath::new();
+        assert_eq!(path.pose_at(0.), None);
+
+        path.record(&camera, 3.);
+        camera.set_pose(&CameraPose::new(Point::new(4., 0., 10.), 0., 270.));
+        path.record(&camera, 5.);
+        // the same moment twice is kept once
+        path.record(&camera, 5.);
+        assert_eq!(path.len(), 2);
+        assert_eq!(path.duration(), 2.);
+
+        // timed from the first pose
+        let pose = path.pose_at(1.).unwrap();
+        assert_eq!(pose.position, Point::new(2., 0., 10.));
+        assert_eq!(path.pose_at(10.).unwrap().position.x, 4.);
+
+        let file = std::env::temp_dir().join("new_rt_camera_path.json");
+        let file = file.to_str().unwrap();
+        path.save(file).unwrap();
+        let loaded = CameraPath::load(file).unwrap();
+        std::fs::remove_file(file).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.pose_at(1.), Some(pose));
+
+        assert!(CameraPath::load("no/such/path.json").is_err());
+    }
+
+    #[test]
+    fn test_record_and_replay_options() {
+        let parse = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            Options::parse(&args)
+        };
+
+        let options = parse(&["a.json", "--record", "fly.json"]).unwrap();
+        assert_eq!(options.record.as_deref(), Some("fly.json"));
+
+        let options = parse(&["a.json", "--replay", "fly.json", "--output", "f.png"]).unwrap();
+        assert_eq!(options.replay.as_deref(), Some("fly.json"));
+
+        assert!(parse(&["a.json", "--replay", "fly.json"]).is_err());
+        assert!(
+            parse(&["a.json", "--replay", "fly.json", "--output", "f.png", "--frames", "0-9"])
+                .is_err()
+        );
+        assert!(parse(&["a.json", "--record", "fly.json", "--output", "f.png"]).is_err());
+    }
+}
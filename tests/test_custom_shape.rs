@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Axis, Bounds, Color, CustomShape, Intersect, LocalIntersection, Material, Object, Pattern,
+        Point, Ray, Shape, Vector, World, register_shape_type,
+    };
+    use serde::{Deserialize, Serialize};
+
+    // square of side `size` lying in the xz plane
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Quad {
+        size: f32,
+    }
+
+    impl Intersect for Quad {
+        fn intersect(&self, ray: Ray) -> Option<LocalIntersection> {
+            if ray.direction.y.abs() < 1e-6 {
+                return None;
+            }
+
+            let t = -ray.origin.y / ray.direction.y;
+            let point = ray.position(t);
+            let hs = self.size / 2.;
+
+            if t < 0. || point.x.abs() > hs || point.z.abs() > hs {
+                return None;
+            }
+
+            Some(LocalIntersection {
+                point,
+                normal: self.normal_at(point),
+                t,
+            })
+        }
+
+        fn normal_at(&self, _point: Point) -> Vector {
+            Vector::new(0., 1., 0.)
+        }
+    }
+
+    impl CustomShape for Quad {
+        fn name(&self) -> &str {
+            "quad"
+        }
+
+        fn bounds(&self) -> Bounds {
+            let hs = self.size / 2.;
+            Bounds::new(Point::new(-hs, -0.01, -hs), Point::new(hs, 0.01, hs))
+        }
+
+        fn uv_at(&self, point: &Point) -> (f32, f32) {
+            (point.x / self.size + 0.5, point.z / self.size + 0.5)
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::to_value(self).unwrap()
+        }
+    }
+
+    // claims a hit for every ray, only its bounds keep it honest
+    #[derive(Debug)]
+    struct Liar;
+
+    impl Intersect for Liar {
+        fn intersect(&self, ray: Ray) -> Option<LocalIntersection> {
+            Some(LocalIntersection {
+                point: ray.position(1.),
+                normal: Vector::new(0., 1., 0.),
+                t: 1.,
+            })
+        }
+
+        fn normal_at(&self, _point: Point) -> Vector {
+            Vector::new(0., 1., 0.)
+        }
+    }
+
+    impl CustomShape for Liar {
+        fn name(&self) -> &str {
+            "liar"
+        }
+
+        fn bounds(&self) -> Bounds {
+            Bounds::new(Point::new(-1., -1., -1.), Point::new(1., 1., 1.))
+        }
+
+        fn uv_at(&self, _point: &Point) -> (f32, f32) {
+            (0., 0.)
+        }
+    }
+
+    #[test]
+    fn test_custom_shape_in_world() {
+        let mut world = World::new();
+        let mut quad = Object::new(Shape::custom(Quad { size: 2. }));
+        quad.position = Point::new(0., -1., 0.);
+        quad.update();
+        world.add_object(quad);
+
+        let ray = Ray::new(Point::new(0.5, 5., 0.5), Vector::new(0., -1., 0.));
+        let hit = world.intersect(&ray, 1.).unwrap();
+        assert_eq!(hit.t, 6.);
+        assert_eq!(hit.point, Point::new(0.5, -1., 0.5));
+
+        let ray = Ray::new(Point::new(1.5, 5., 0.), Vector::new(0., -1., 0.));
+        assert!(world.intersect(&ray, 1.).is_none());
+    }
+
+    #[test]
+    fn test_bounds_reject_rays_before_intersect() {
+        let shape = Shape::custom(Liar);
+
+        let inside = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
+        assert!(shape.intersect(inside).is_some());
+
+        let outside = Ray::new(Point::new(0., 3., -5.), Vector::new(0., 0., 1.));
+        assert!(shape.intersect(outside).is_none());
+
+        let behind = Ray::new(Point::new(0., 0., 5.), Vector::new(0., 0., 1.));
+        assert!(shape.intersect(behind).is_none());
+    }
+
+    #[test]
+    fn test_custom_shape_uv() {
+        let quad = Quad { size: 4. };
+
+        assert_eq!(quad.uv_at(&Point::new(0., 0., 0.)), (0.5, 0.5));
+        assert_eq!(quad.uv_at(&Point::new(-2., 0., 2.)), (0., 1.));
+
+        // the pattern goes along u, from one edge of the quad to the other
+        let pattern = Pattern::new(Color::WHITE, Color::BLACK, Axis::X, true);
+        let mut material = Material::new();
+        material.pattern = Some(pattern);
+        let object = Object::new(Shape::custom(quad)).material(material);
+
+        let left = object.color_at(&Point::new(-1., 0., -1.));
+        assert_eq!(left.red(), 0.75);
+        assert_eq!(object.color_at(&Point::new(-1., 0., 1.)).red(), left.red());
+        assert_eq!(object.color_at(&Point::new(1., 0., 0.)).red(), 0.25);
+    }
+
+    #[test]
+    fn test_custom_shape_from_scene_json() {
+        register_shape_type::<Quad>("quad");
+
+        let shape: Shape =
+            serde_json::from_str(r#"{ "Custom": { "type": "quad", "size": 3.0 } }"#).unwrap();
+        let ray = Ray::new(Point::new(1.4, 1., 0.), Vector::new(0., -1., 0.));
+        assert!(shape.intersect(ray).is_some());
+
+        let saved = serde_json::to_value(&shape).unwrap();
+        assert_eq!(saved["Custom"]["type"], "quad");
+        assert_eq!(saved["Custom"]["size"], 3.);
+
+        let unknown = serde_json::from_str::<Shape>(r#"{ "Custom": { "type": "blob" } }"#);
+        assert!(unknown.is_err());
+
+        let builtin: Shape = serde_json::from_str(r#"{ "Sphere": { "radius": 1.0 } }"#).unwrap();
+        assert!(matches!(builtin, Shape::Sphere(_)));
+    }
+}
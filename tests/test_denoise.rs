@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Adaptive, Camera, Canvas, Color, Denoiser, Integrator, Light, NewCamera, NewCanvas, Object,
+        Options, Plane, Point, RenderSettings, Renderer, Rng, Shape, Vector, World,
+    };
+
+    fn canvas(width: usize, height: usize, mut color: impl FnMut(usize, usize) -> Color) -> Canvas {
+        let mut canvas = Canvas::new(NewCanvas { width, height });
+        for y in 0..height {
+            for x in 0..width {
+                canvas.write(x, y, color(x, y));
+            }
+        }
+
+        canvas
+    }
+
+    fn flat(value: f32) -> Canvas {
+        canvas(16, 16, |_, _| Color::new(value, value, value))
+    }
+
+    // mean and variance of the red channel over columns
+    fn stats(canvas: &Canvas, columns: std::ops::Range<usize>) -> (f32, f32) {
+        let values: Vec<f32> = (0..canvas.height)
+            .flat_map(|y| columns.clone().map(move |x| (x, y)))
+            .map(|(x, y)| canvas.at(x, y).red())
+            .collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance =
+            values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+
+        (mean, variance)
+    }
+
+    #[test]
+    fn test_noise_goes_away() {
+        let mut rng = Rng::new(7);
+        let noisy = canvas(16, 16, |_, _| {
+            let v = 0.5 + (rng.next_f32() - 0.5) * 0.2;
+            Color::new(v, v, v)
+        });
+        let denoised = Denoiser::default().apply(&noisy, &flat(0.5), &flat(0.), &flat(5.));
+
+        let (mean, variance) = stats(&noisy, 0..16);
+        let (denoised_mean, denoised_variance) = stats(&denoised, 0..16);
+        assert!(denoised_variance < variance / 4.);
+        assert!((denoised_mean - mean).abs() < 0.01);
+
+        // nothing to do without a radius
+        let same = Denoiser::new(0).apply(&noisy, &flat(0.5), &flat(0.), &flat(5.));
+        assert_eq!(same.at(3, 4).red(), noisy.at(3, 4).red());
+    }
+
+    #[test]
+    fn test_edges_stay_sharp() {
+        let mut rng = Rng::new(3);
+        let noisy = canvas(16, 16, |x, _| {
+            let base = if x < 8 { 0.2 } else { 0.8 };
+            let v = base + (rng.next_f32() - 0.5) * 0.1;
+            Color::new(v, v, v)
+        });
+
+        // a change of material
+        let albedo = canvas(
+            16,
+            16,
+            |x, _| if x < 8 { Color::BLACK } else { Color::WHITE },
+        );
+        let denoised = Denoiser::default().apply(&noisy, &albedo, &flat(0.), &flat(5.));
+        assert!((stats(&denoised, 7..8).0 - 0.2).abs() < 0.02);
+        assert!((stats(&denoised, 8..9).0 - 0.8).abs() < 0.02);
+
+        // an object in front of the background
+        let depth = canvas(16, 16, |x, _| {
+            let z = if x < 8 { 2. } else { f32::INFINITY };
+            Color::new(z, z, z)
+        });
+        let denoised = Denoiser::default().apply(&noisy, &flat(0.5), &flat(0.), &depth);
+        assert!((stats(&denoised, 7..8).0 - 0.2).abs() < 0.02);
+        assert!((stats(&denoised, 8..9).0 - 0.8).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_denoised_render() {
+        let mut world = World::new();
+        world.add_object(Object::new(Shape::Plane(Plane::new())));
+        world.add_light(Light::new(Point::new(0., 10., 0.), Color::WHITE));
+
+        let render = |denoise: Option<Denoiser>, adaptive: Option<Adaptive>| {
+            let canvas = Canvas::new(NewCanvas {
+                width: 16,
+                height: 16,
+            });
+            let mut renderer = Renderer::headless(canvas, world.clone());
+            // looking down at the lit floor
+            let settings = NewCamera {
+                position: Point::new(0., 5., 0.),
+                direction: Vector::new(0., -1., 0.),
+                fov: 30.,
+                ..NewCamera::default()
+            };
+            renderer.camera = Camera::from_settings(&settings, 1.);
+            renderer.settings.integrator = Integrator::PathTracer;
+            renderer.settings.denoise = denoise;
+            renderer.settings.adaptive = adaptive;
+            renderer.update_image();
+
+            stats(&renderer.canvas, 4..12).1
+        };
+
+        assert!(render(Some(Denoiser::default()), None) < render(None, None));
+        // the guides come from the first sample of the pixels either way
+        let adaptive = Some(Adaptive::default());
+        assert!(render(Some(Denoiser::default()), adaptive.clone()) < render(None, adaptive));
+    }
+
+    #[test]
+    fn test_denoiser_from_scene_and_command_line() {
+        let settings: RenderSettings = serde_json::from_str(r#"{ "denoise": {} }"#).unwrap();
+        assert_eq!(settings.denoise, Some(Denoiser::default()));
+        assert!(RenderSettings::default().denoise.is_none());
+
+        let apply = |args: &[&str], settings: &mut RenderSettings| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            Options::parse(&args).unwrap().apply(settings);
+        };
+        let mut settings = RenderSettings::default();
+        apply(&["a.json", "--denoise", "2"], &mut settings);
+        assert_eq!(settings.denoise, Some(Denoiser::new(2)));
+
+        apply(&["a.json", "--denoise", "0"], &mut settings);
+        assert!(settings.denoise.is_none());
+    }
+}
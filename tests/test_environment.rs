@@ -0,0 +1,234 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Ambient, Background, Canvas, Color, EnvironmentMap, Material, NewCanvas, Object, Plane,
+        Point, Ray, Renderer, Rng, Shape, Vector, World,
+    };
+
+    #[test]
+    fn test_default_world_settings() {
+        let world: World = serde_json::from_str(r#"{ "objects": [], "lights": [] }"#).unwrap();
+
+        let ambient = world.ambient.light();
+        assert_eq!(ambient.red(), 0.1);
+        assert_eq!(ambient.green(), 0.1);
+        assert_eq!(ambient.blue(), 0.1);
+
+        let sky = world.background.color_at(&Vector::new(0., 1., 0.));
+        assert_eq!(sky.red(), 0.);
+    }
+
+    #[test]
+    fn test_ambient_color_and_intensity() {
+        let ambient = Ambient::new(Color::new(1., 0.5, 0.), 0.2);
+
+        assert_eq!(ambient.light().red(), 0.2);
+        assert_eq!(ambient.light().green(), 0.1);
+        assert_eq!(ambient.light().blue(), 0.);
+    }
+
+    #[test]
+    fn test_solid_background() {
+        let background = Background::Solid(Color::new(0.2, 0.3, 0.4));
+
+        let color = background.color_at(&Vector::new(1., -1., 0.));
+        assert_eq!(color.red(), 0.2);
+        assert_eq!(color.blue(), 0.4);
+    }
+
+    #[test]
+    fn test_vertical_gradient_background() {
+        let background = Background::Gradient {
+            top: Color::new(0., 0., 1.),
+            bottom: Color::new(1., 1., 1.),
+        };
+
+        let up = background.color_at(&Vector::new(0., 1., 0.));
+        assert_eq!(up.red(), 0.);
+        assert_eq!(up.blue(), 1.);
+
+        let down = background.color_at(&Vector::new(0., -2., 0.));
+        assert_eq!(down.red(), 1.);
+
+        let horizon = background.color_at(&Vector::new(0., 0., -1.));
+        assert_eq!(horizon.red(), 0.5);
+    }
+
+    #[test]
+    fn test_sun_and_sky_background() {
+        let sun = Vector::new(0., 1., -1.).normalize();
+        let background = Background::sun_sky(sun);
+
+        let at_sun = background.color_at(&sun);
+        let zenith = background.color_at(&Vector::new(0., 1., 0.));
+        let horizon = background.color_at(&Vector::new(1., 0.01, 0.));
+        let ground = background.color_at(&Vector::new(1., -1., 0.));
+
+        assert!(at_sun.luminance() > 1.);
+        // the sky is bluer up than at the horizon
+        assert!(zenith.blue() - zenith.red() > horizon.blue() - horizon.red());
+        assert!(ground.luminance() < horizon.luminance());
+    }
+
+    #[test]
+    fn test_background_from_json() {
+        let world: World = serde_json::from_str(
+            r#"{
+                "ambient": { "color": { "r": 1.0, "g": 1.0, "b": 1.0 }, "intensity": 0.05 },
+                "background": { "SunSky": { "sun_direction": { "x": 0.0, "y": 1.0, "z": 0.0 } } },
+                "objects": [],
+                "lights": []
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(world.ambient.intensity, 0.05);
+        assert!(matches!(world.background, Background::SunSky { .. }));
+    }
+
+    fn two_rows(top: Color, bottom: Color) -> EnvironmentMap {
+        EnvironmentMap::from_pixels(2, 2, vec![top, top, bottom, bottom])
+    }
+
+    #[test]
+    fn test_environment_map_lookup() {
+        let map = two_rows(Color::new(1., 0., 0.), Color::new(0., 0., 1.)).intensity(2.);
+
+        let up = map.radiance(&Vector::new(0., 1., 0.));
+        assert_eq!(up.red(), 2.);
+        assert_eq!(up.blue(), 0.);
+
+        let down = map.radiance(&Vector::new(0.3, -1., 0.2));
+        assert_eq!(down.blue(), 2.);
+
+        let background = Background::Environment(map);
+        assert_eq!(background.color_at(&Vector::new(0., 5., 0.)).red(), 2.);
+    }
+
+    #[test]
+    fn test_environment_map_rotation() {
+        let red = Color::new(1., 0., 0.);
+        let green = Color::new(0., 1., 0.);
+        let map = EnvironmentMap::from_pixels(2, 1, vec![red, green]);
+
+        // straight ahead is the middle of the picture
+        let ahead = Vector::new(0., 0., -1.);
+        assert_eq!(map.radiance(&ahead).green(), 1.);
+
+        let map = map.rotation(180.);
+        assert_eq!(map.radiance(&ahead).red(), 1.);
+    }
+
+    #[test]
+    fn test_environment_sampling_follows_brightness() {
+        let mut pixels = vec![Color::new(0.1, 0.1, 0.1); 8 * 4];
+        pixels[8 + 5] = Color::new(100., 100., 100.);
+        let map = EnvironmentMap::from_pixels(8, 4, pixels);
+
+        let mut bright = 0;
+        for i in 0..100 {
+            let s = (i % 10) as f32 / 10. + 0.05;
+            let t = (i / 10) as f32 / 10. + 0.05;
+            let (direction, radiance, pdf) = map.sample(s, t).unwrap();
+
+            if radiance.red() == 100. {
+                bright += 1;
+            }
+            assert!((pdf - map.pdf(&direction)).abs() < pdf * 0.01);
+        }
+
+        assert!(bright > 90);
+    }
+
+    #[test]
+    fn test_black_environment_is_not_sampled() {
+        let map = EnvironmentMap::from_pixels(4, 2, vec![Color::BLACK; 8]);
+
+        assert!(map.sample(0.5, 0.5).is_none());
+        assert_eq!(map.pdf(&Vector::new(0., 1., 0.)), 0.);
+    }
+
+    #[test]
+    fn test_image_based_lighting_of_a_diffuse_floor() {
+        let mut world = World::new();
+        world.background = Background::Environment(
+            EnvironmentMap::from_pixels(8, 4, vec![Color::WHITE; 32]).samples(512),
+        );
+
+        let material = Material::new().diffuse(0.9).specular(0.);
+        let mut floor = Object::new(Shape::Plane(Plane::new())).material(material);
+        floor.update();
+        world.add_object(floor.clone());
+
+        let point = Point::new(0., 0.01, 0.);
+        let up = Vector::new(0., 1., 0.);
+
+        // white sky all around: all the albedo comes back
+        let color = world.image_based_lighting(&floor, &point, &up, &up, 0.);
+        assert!((color.red() - 0.9).abs() < 0.05);
+
+        // something right above the floor hides the sky
+        let mut ceiling = Object::new(Shape::Plane(Plane::new()));
+        ceiling.position = Point::new(0., 1., 0.);
+        ceiling.update();
+        world.add_object(ceiling);
+
+        let color = world.image_based_lighting(&floor, &point, &up, &up, 0.);
+        assert!(color.red() < 0.01);
+    }
+
+    #[test]
+    fn test_environment_lighting_is_asked_for() {
+        let mut world = World::new();
+        world.ambient = Ambient::new(Color::WHITE, 0.);
+        world.background = Background::Environment(EnvironmentMap::from_pixels(
+            8,
+            4,
+            vec![Color::WHITE; 32],
+        ));
+        world.add_object(Object::new(Shape::Plane(Plane::new())));
+
+        let ray = Ray::new(Point::new(0., 1., 0.), Vector::new(0., -1., 0.));
+        let canvas = || {
+            Canvas::new(NewCanvas {
+                width: 1,
+                height: 1,
+            })
+        };
+
+        let mut renderer = Renderer::headless(canvas(), world);
+        assert!(renderer.trace(&ray, &mut Rng::new(0)).0.is_black());
+
+        renderer.settings.environment_lighting = true;
+        assert!(renderer.trace(&ray, &mut Rng::new(0)).0.red() > 0.5);
+    }
+
+    #[test]
+    fn test_environment_map_from_scene_file() {
+        let dir = std::env::temp_dir().join("new_rt_test_environment");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut picture = image::RgbImage::new(2, 1);
+        picture.put_pixel(0, 0, image::Rgb([255, 128, 0]));
+        picture.put_pixel(1, 0, image::Rgb([255, 128, 0]));
+        picture.save(dir.join("sky.png")).unwrap();
+
+        let mut world: World = serde_json::from_str(
+            r#"{
+                "background": { "Environment": { "path": "sky.png", "intensity": 0.5 } },
+                "objects": [],
+                "lights": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(world.background.color_at(&Vector::new(0., 1., 0.)).red(), 0.);
+
+        world.load_resources(Some(&dir)).unwrap();
+        let color = world.background.color_at(&Vector::new(0., 1., 0.));
+        assert_eq!(color.red(), 0.5);
+        // srgb pixels are turned back into linear light
+        assert!(color.green() > 0.1 && color.green() < 0.15);
+
+        let mut missing = Background::Environment(EnvironmentMap::new("nowhere.hdr"));
+        assert!(missing.load(Some(&dir)).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Adaptive, Color, Film, Filter, Options, PixelSampler, RenderSettings, Rng, contrast_mask,
+        halton,
+    };
+
+    #[test]
+    fn test_halton_sequence() {
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(2, 2), 0.25);
+        assert_eq!(halton(3, 2), 0.75);
+        assert!((halton(1, 3) - 1. / 3.).abs() < 1e-6);
+        assert!((halton(2, 3) - 2. / 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pixel_samplers() {
+        let mut rng = Rng::new(7);
+
+        // a single stratified sample is the pixel center, like before supersampling
+        assert_eq!(
+            PixelSampler::Stratified.offsets(1, &mut rng),
+            vec![(0.5, 0.5)]
+        );
+
+        let grid = PixelSampler::Stratified.offsets(4, &mut rng);
+        assert_eq!(
+            grid,
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+
+        // one jittered sample in each cell of the grid
+        let jittered = PixelSampler::Jittered.offsets(5, &mut rng);
+        assert_eq!(jittered.len(), 9);
+        for (i, (x, y)) in jittered.iter().enumerate() {
+            assert_eq!((x * 3.) as usize, i % 3);
+            assert_eq!((y * 3.) as usize, i / 3);
+        }
+
+        let halton = PixelSampler::Halton.offsets(5, &mut rng);
+        assert_eq!(halton.len(), 5);
+        assert!(
+            halton
+                .iter()
+                .all(|(x, y)| (0. ..1.).contains(x) && (0. ..1.).contains(y))
+        );
+    }
+
+    #[test]
+    fn test_refinement_keeps_to_the_budget() {
+        let mut rng = Rng::new(3);
+        for sampler in [
+            PixelSampler::Stratified,
+            PixelSampler::Jittered,
+            PixelSampler::Halton,
+        ] {
+            // 15 more after the center for a budget of 16, not the 16 of the grid
+            for count in 1..20 {
+                assert_eq!(sampler.shuffled_offsets(count, &mut rng).len(), count);
+            }
+        }
+
+        // the first batch of 4 is not only the top row of the grid
+        for seed in 0..8 {
+            let offsets = PixelSampler::Stratified.shuffled_offsets(15, &mut Rng::new(seed));
+            let row = (offsets[0].1 * 4.) as usize;
+            assert!(offsets[..4].iter().any(|(_, y)| (y * 4.) as usize != row));
+        }
+    }
+
+    #[test]
+    fn test_filter_weights() {
+        assert_eq!(Filter::Box.weight(0.4, -0.4, 0.5), 1.);
+        assert_eq!(Filter::Box.weight(0.6, 0., 0.5), 0.);
+
+        assert_eq!(Filter::Tent.weight(0., 0., 1.), 1.);
+        assert_eq!(Filter::Tent.weight(0.5, 0., 1.), 0.5);
+
+        let center = Filter::Gaussian.weight(0., 0., 1.5);
+        assert!(center > Filter::Gaussian.weight(0.5, 0., 1.5));
+        assert_eq!(Filter::Gaussian.weight(1.5, 0., 1.5), 0.);
+
+        // mitchell goes slightly negative before going back to 0
+        assert!(Filter::Mitchell.weight(0., 0., 2.) > 0.);
+        assert!(Filter::Mitchell.weight(1.5, 0., 2.) < 0.);
+        assert_eq!(Filter::Mitchell.weight(2., 0., 2.), 0.);
+    }
+
+    #[test]
+    fn test_box_film_averages_the_pixel_samples() {
+        let mut film = Film::new(2, 1, Filter::Box, 0.5);
+
+        film.add_sample(0.25, 0.5, Color::new(1., 0., 0.));
+        film.add_sample(0.75, 0.5, Color::new(0., 0., 1.));
+        film.add_sample(1.5, 0.5, Color::new(0., 1., 0.));
+
+        let left = film.pixel(0, 0);
+        assert_eq!(left.red(), 0.5);
+        assert_eq!(left.blue(), 0.5);
+        assert_eq!(left.green(), 0.);
+
+        assert_eq!(film.pixel(1, 0).green(), 1.);
+    }
+
+    #[test]
+    fn test_wide_filter_shares_samples_with_neighbours() {
+        let mut film = Film::new(3, 1, Filter::Tent, 1.);
+
+        film.add_sample(0.5, 0.5, Color::new(1., 1., 1.));
+        film.add_sample(2.5, 0.5, Color::new(0., 0., 0.));
+        film.add_sample(1.5, 0.5, Color::new(0.5, 0.5, 0.5));
+
+        // the tent reaches 0 at the next pixel center, so these stay apart
+        assert_eq!(film.pixel(0, 0).red(), 1.);
+        assert_eq!(film.pixel(1, 0).red(), 0.5);
+
+        film.add_sample(1., 0.5, Color::new(1., 1., 1.));
+        let middle = film.pixel(1, 0).red();
+        assert!(middle > 0.5 && middle < 1.);
+        assert!(film.pixel(2, 0).red() == 0.);
+    }
+
+    #[test]
+    fn test_render_settings_from_json() {
+        let settings: RenderSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.samples, 1);
+        assert_eq!(settings.sampler, PixelSampler::Stratified);
+        assert_eq!(settings.filter_radius(), 0.5);
+
+        let settings: RenderSettings =
+            serde_json::from_str(r#"{ "samples": 16, "sampler": "Halton", "filter": "Mitchell" }"#)
+                .unwrap();
+        assert_eq!(settings.samples, 16);
+        assert_eq!(settings.filter, Filter::Mitchell);
+        assert_eq!(settings.filter_radius(), 2.);
+    }
+
+    #[test]
+    fn test_command_line_overrides_the_scene() {
+        let args: Vec<String> = ["maps/sun.json", "--samples", "9", "--filter", "gaussian"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = Options::parse(&args).unwrap();
+        assert_eq!(options.map, "maps/sun.json");
+
+        let mut settings = RenderSettings {
+            sampler: PixelSampler::Jittered,
+            ..RenderSettings::default()
+        };
+        options.apply(&mut settings);
+        assert_eq!(settings.samples, 9);
+        assert_eq!(settings.filter, Filter::Gaussian);
+        assert_eq!(settings.sampler, PixelSampler::Jittered);
+
+        let bad = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            Options::parse(&args).is_err()
+        };
+        assert!(bad(&[]));
+        assert!(bad(&["a.json", "--samples"]));
+        assert!(bad(&["a.json", "--samples", "many"]));
+        assert!(bad(&["a.json", "--filter", "lanczos"]));
+        assert!(bad(&["a.json", "b.json"]));
+    }
+
+    #[test]
+    fn test_contrast_mask_finds_color_edges() {
+        let dark = Color::new(0.1, 0.1, 0.1);
+        let light = Color::new(0.9, 0.9, 0.9);
+        let colors = [dark, dark, light, light, dark, dark, light, light];
+        let ids = [None; 8];
+
+        let mask = contrast_mask(4, 2, &colors, &ids, 0.1);
+        assert_eq!(
+            mask,
+            vec![false, true, true, false, false, true, true, false]
+        );
+
+        // small differences and overexposed pixels are not edges
+        let colors = [
+            Color::new(0.5, 0.5, 0.5),
+            Color::new(0.55, 0.5, 0.5),
+            Color::new(3., 3., 3.),
+            Color::new(9., 9., 9.),
+        ];
+        let mask = contrast_mask(2, 2, &colors[..], &[None; 4], 0.1);
+        assert_eq!(mask, vec![true, true, true, true]);
+        let mask = contrast_mask(2, 1, &colors[2..], &[None; 2], 0.1);
+        assert_eq!(mask, vec![false, false]);
+    }
+
+    #[test]
+    fn test_contrast_mask_finds_object_boundaries() {
+        let gray = Color::new(0.5, 0.5, 0.5);
+        let colors = [gray; 3];
+
+        // same color, but not the same object in the middle
+        let mask = contrast_mask(3, 1, &colors, &[Some(1), Some(2), Some(2)], 0.1);
+        assert_eq!(mask, vec![true, true, false]);
+
+        let mask = contrast_mask(3, 1, &colors, &[None, None, Some(2)], 0.1);
+        assert_eq!(mask, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_adaptive_settings() {
+        let settings: RenderSettings =
+            serde_json::from_str(r#"{ "adaptive": { "max_samples": 32 } }"#).unwrap();
+        let adaptive = settings.adaptive.unwrap();
+        assert_eq!(adaptive.max_samples, 32);
+        assert_eq!(adaptive.threshold, 0.1);
+
+        let args: Vec<String> = ["a.json", "--threshold", "0.05"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut settings = RenderSettings::default();
+        Options::parse(&args).unwrap().apply(&mut settings);
+        assert_eq!(
+            settings.adaptive,
+            Some(Adaptive {
+                threshold: 0.05,
+                max_samples: 16
+            })
+        );
+    }
+}
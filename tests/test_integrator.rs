@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Background, Canvas, Color, Cube, Emission, EnvironmentMap, Integrator, Light, LightModel,
+        Material, NewCanvas, Object, Options, PathTracer, Plane, Point, Ray, RenderSettings,
+        Renderer, Rng, Shape, Sphere, Vector, World,
+    };
+
+    fn floor(material: Material) -> Object {
+        let mut floor = Object::new(Shape::Plane(Plane::new())).material(material);
+        floor.update();
+
+        floor
+    }
+
+    fn average(world: &World, ray: &Ray, samples: usize) -> Color {
+        let tracer = PathTracer::new(8);
+        let mut rng = Rng::new(42);
+        let mut sum = Color::BLACK;
+
+        for _ in 0..samples {
+            sum += tracer.radiance(world, ray, &mut rng);
+        }
+
+        sum * (1. / samples as f32)
+    }
+
+    #[test]
+    fn test_direct_light_matches_phong() {
+        let mut world = World::new();
+        world.light_model = LightModel::Physical;
+        world.add_object(floor(Material::new().diffuse(0.9).specular(0.)));
+        world.add_light(Light::new(Point::new(0., 2., 0.), Color::new(1., 1., 1.)));
+
+        let ray = Ray::new(Point::new(0., 1., 0.), Vector::new(0., -1., 0.));
+        let color = PathTracer::new(8).radiance(&world, &ray, &mut Rng::new(1));
+
+        // 0.9 * cos(0) / 2^2, nothing else to bounce on
+        assert!((color.red() - 0.225).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_white_furnace() {
+        let mut world = World::new();
+        world.background =
+            Background::Environment(EnvironmentMap::from_pixels(8, 4, vec![Color::WHITE; 32]));
+        world.add_object(floor(Material::new().diffuse(0.9).specular(0.)));
+
+        // the floor sends back its albedo of the uniform sky
+        let ray = Ray::new(Point::new(0., 1., 0.), Vector::new(0., -1., 0.));
+        let color = average(&world, &ray, 2000);
+        assert!((color.red() - 0.9).abs() < 0.05);
+
+        let sky = Ray::new(Point::new(0., 1., 0.), Vector::new(0., 1., 0.));
+        assert_eq!(average(&world, &sky, 1).red(), 1.);
+    }
+
+    #[test]
+    fn test_color_bleeding() {
+        let mut world = World::new();
+        world.add_object(floor(Material::new().specular(0.)));
+
+        let mut ball = Object::new(Shape::Sphere(Sphere::new(1.)))
+            .material(Material::new().color(Color::new(1., 0., 0.)).specular(0.));
+        ball.position = Point::new(1.2, 1., 0.);
+        ball.update();
+        world.add_object(ball);
+        world.add_light(Light::new(
+            Point::new(-2., 5., 0.),
+            Color::new(20., 20., 20.),
+        ));
+
+        let ray = Ray::new(Point::new(0., 0.5, 1.), Vector::new(0., -0.5, -1.));
+
+        // the light bounced by the red ball tints the white floor
+        let color = average(&world, &ray, 500);
+        assert!(color.red() > color.green() * 1.05);
+
+        // no indirect light with phong
+        let direct = PathTracer::new(1).radiance(&world, &ray, &mut Rng::new(3));
+        assert!((direct.red() - direct.green()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_glass_lets_light_through() {
+        let mut world = World::new();
+        world.add_object(floor(Material::new().specular(0.)));
+
+        let mut pane = floor(
+            Material::new()
+                .color(Color::new(0., 1., 0.))
+                .specular(0.)
+                .transparency(1.),
+        );
+        pane.position = Point::new(0., 1., 0.);
+        pane.update();
+        world.add_object(pane);
+        world.add_light(Light::new(Point::new(0., 2., 0.), Color::new(1., 1., 1.)));
+
+        // the floor is lit through the green pane, and seen through it
+        let ray = Ray::new(Point::new(0., 1.5, 0.), Vector::new(0., -1., 0.));
+        let color = average(&world, &ray, 200);
+        assert!(color.green() > 0.1);
+        assert!(color.red() < 0.01);
+    }
+
+    #[test]
+    fn test_integrator_settings() {
+        let settings: RenderSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.integrator, Integrator::Whitted);
+        assert_eq!(settings.max_depth, 8);
+
+        let settings: RenderSettings =
+            serde_json::from_str(r#"{ "integrator": "PathTracer", "max_depth": 4 }"#).unwrap();
+        assert_eq!(settings.integrator, Integrator::PathTracer);
+
+        let args: Vec<String> = ["a.json", "--integrator", "path", "--max-depth", "12"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut settings = RenderSettings::default();
+        Options::parse(&args).unwrap().apply(&mut settings);
+        assert_eq!(settings.integrator, Integrator::PathTracer);
+        assert_eq!(settings.max_depth, 12);
+    }
+
+    fn lamp(position: Point, radius: f32, strength: f32) -> Object {
+        let material = Material::new()
+            .diffuse(0.)
+            .specular(0.)
+            .emission(Emission::new(Color::WHITE, strength));
+        let mut lamp = Object::new(Shape::Sphere(Sphere::new(radius))).material(material);
+        lamp.position = position;
+        lamp.update();
+
+        lamp
+    }
+
+    #[test]
+    fn test_tinted_slab_tints_once() {
+        let mut world = World::new();
+        world.background =
+            Background::Environment(EnvironmentMap::from_pixels(8, 4, vec![Color::WHITE; 32]));
+        let glass = Material::new()
+            .color(Color::new(0.5, 1., 1.))
+            .transparency(1.)
+            .refractive_index(1.);
+        let mut slab = Object::new(Shape::Cube(Cube::new(2.))).material(glass);
+        slab.scale = Vector::new(4., 0.1, 4.);
+        slab.update();
+        world.add_object(slab);
+
+        // in and out of the slab, straight through to the sky
+        let ray = Ray::new(Point::new(0., 1., 0.), Vector::new(0., -1., 0.));
+        let color = PathTracer::new(8).radiance(&world, &ray, &mut Rng::new(1));
+        assert!((color.red() - 0.5).abs() < 1e-4);
+        assert!((color.green() - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_emissive_object_is_seen() {
+        let mut world = World::new();
+        world.add_object(lamp(Point::new(0., 0., 0.), 1., 5.));
+
+        let ray = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
+        let color = PathTracer::new(8).radiance(&world, &ray, &mut Rng::new(1));
+        assert_eq!(color.red(), 5.);
+    }
+
+    #[test]
+    fn test_emissive_object_lights_the_floor() {
+        let mut world = World::new();
+        world.add_object(floor(Material::new().diffuse(0.9).specular(0.)));
+        world.add_object(lamp(Point::new(0., 2., 0.), 0.5, 10.));
+
+        // albedo * radiance * sin^2 of the angle under which the lamp is seen
+        let expected = 0.9 * 10. * (0.5f32 / 2.).powi(2);
+
+        let ray = Ray::new(Point::new(0.1, 1., 1.), Vector::new(-0.1, -1., -1.));
+        let color = average(&world, &ray, 500);
+        assert!((color.red() - expected).abs() < expected * 0.05);
+
+        // only found by the bounces when it is a plane, still the same light in the end
+        let mut world = World::new();
+        world.add_object(floor(Material::new().diffuse(0.9).specular(0.)));
+        let mut ceiling = floor(
+            Material::new()
+                .diffuse(0.)
+                .specular(0.)
+                .emission(Emission::new(Color::WHITE, 1.)),
+        );
+        ceiling.position = Point::new(0., 3., 0.);
+        ceiling.update();
+        world.add_object(ceiling);
+
+        let color = average(&world, &ray, 2000);
+        assert!((color.red() - 0.9).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_mirror_shows_the_emitter() {
+        let mut world = World::new();
+        world.add_object(floor(
+            Material::new()
+                .diffuse(0.)
+                .specular(0.)
+                .ambient(0.)
+                .reflective(1.),
+        ));
+        world.add_object(lamp(Point::new(0., 3., 0.), 0.5, 3.));
+        let renderer = Renderer::headless(
+            Canvas::new(NewCanvas {
+                width: 1,
+                height: 1,
+            }),
+            world,
+        );
+
+        // straight down on the mirror, straight up into the lamp, no light in the world
+        let ray = Ray::new(Point::new(0., 1., 0.), Vector::new(0., -1., 0.));
+        let hit = renderer.world.intersect(&ray, 1.).unwrap();
+        let color = renderer.reflected_color(&hit);
+        assert!((color.red() - 3.).abs() < 1e-4);
+        assert!((color.blue() - 3.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_emission_from_json() {
+        let emission: Emission =
+            serde_json::from_str(r#"{ "color": { "r": 1.0, "g": 0.5, "b": 0.0 } }"#).unwrap();
+        assert_eq!(emission.strength, 1.);
+        assert_eq!(emission.radiance().green(), 0.5);
+
+        assert!(Material::new().emitted().is_black());
+
+        let negative = serde_json::from_str::<Emission>(
+            r#"{ "color": { "r": 1.0, "g": 1.0, "b": 1.0 }, "strength": -1.0 }"#,
+        );
+        assert!(negative.is_err());
+    }
+}
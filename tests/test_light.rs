@@ -1,16 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use new_rt::{Color, Light, Material, Object, Point, Shape, Sphere, Vector, World};
+    use new_rt::{
+        AreaShape, Attenuation, Canvas, Color, Light, LightKind, LightModel, Material, NewCanvas,
+        Object, Point, Ray, Renderer, Rng, Shape, Sphere, Vector, World,
+    };
 
     #[test]
     fn test_light_pos_and_intensity() {
         let intensity = Color::new(1., 1., 1.);
         let position = Point::new(0., 0., 0.);
 
-        let light = Light {
-            position,
-            intensity,
-        };
+        let light = Light::new(position, intensity);
 
         assert_eq!(light.position.x, 0.);
         assert_eq!(light.position.y, 0.);
@@ -28,10 +28,7 @@ mod tests {
         let eyev = Vector::new(0., 0., -1.);
         let normalv = Vector::new(0., 0., -1.);
 
-        let light = Light {
-            position: Point::new(0., 0., -10.),
-            intensity: Color::new(1., 1., 1.),
-        };
+        let light = Light::new(Point::new(0., 0., -10.), Color::new(1., 1., 1.));
 
         let result = World::lighting(&obj, &light, &position, &eyev, &normalv, false);
 
@@ -48,10 +45,7 @@ mod tests {
         let eyev = Vector::new(0., (2.0_f32).sqrt() / 2.0, (2.0_f32).sqrt() / 2.0);
         let normalv = Vector::new(0., 0., -1.);
 
-        let light = Light {
-            position: Point::new(0., 0., -10.),
-            intensity: Color::new(1., 1., 1.),
-        };
+        let light = Light::new(Point::new(0., 0., -10.), Color::new(1., 1., 1.));
 
         let result = World::lighting(&obj, &light, &position, &eyev, &normalv, false);
 
@@ -68,10 +62,7 @@ mod tests {
         let eyev = Vector::new(0., 0., -1.);
         let normalv = Vector::new(0., 0., -1.);
 
-        let light = Light {
-            position: Point::new(0., 10., -10.),
-            intensity: Color::new(1., 1., 1.),
-        };
+        let light = Light::new(Point::new(0., 10., -10.), Color::new(1., 1., 1.));
 
         let result = World::lighting(&obj, &light, &position, &eyev, &normalv, false);
 
@@ -88,10 +79,7 @@ mod tests {
         let eyev = Vector::new(0., -(2.0_f32.sqrt() / 2.0), -(2.0_f32.sqrt() / 2.0));
         let normalv = Vector::new(0., 0., -1.);
 
-        let light = Light {
-            position: Point::new(0., 10., -10.),
-            intensity: Color::new(1., 1., 1.),
-        };
+        let light = Light::new(Point::new(0., 10., -10.), Color::new(1., 1., 1.));
 
         let result = World::lighting(&obj, &light, &position, &eyev, &normalv, false);
 
@@ -108,10 +96,7 @@ mod tests {
         let eyev = Vector::new(0., 0., -1.);
         let normalv = Vector::new(0., 0., -1.);
 
-        let light = Light {
-            position: Point::new(0., 0., 10.),
-            intensity: Color::new(1., 1., 1.),
-        };
+        let light = Light::new(Point::new(0., 0., 10.), Color::new(1., 1., 1.));
 
         let result = World::lighting(&obj, &light, &position, &eyev, &normalv, false);
 
@@ -119,4 +104,298 @@ mod tests {
         assert_eq!(result.green(), 0.1);
         assert_eq!(result.blue(), 0.1);
     }
+
+    #[test]
+    fn test_directional_light_rays_are_parallel() {
+        let light = Light::directional(Vector::new(0., -2., 0.), Color::new(1., 1., 1.));
+
+        assert_eq!(light.direction_from(&Point::new(0., 0., 0.)), Vector::new(0., 1., 0.));
+        assert_eq!(light.direction_from(&Point::new(50., -3., 7.)), Vector::new(0., 1., 0.));
+        assert_eq!(light.distance_from(&Point::new(50., -3., 7.)), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_directional_light_has_no_falloff() {
+        let obj = Object::new(Shape::Sphere(Sphere::new(1.))).material(Material::new());
+
+        let eyev = Vector::new(0., 0., -1.);
+        let normalv = Vector::new(0., 0., -1.);
+
+        let light = Light::directional(Vector::new(0., 0., 1.), Color::new(1., 1., 1.));
+
+        let near = World::lighting(&obj, &light, &Point::new(0., 0., 0.), &eyev, &normalv, false);
+        let far = World::lighting(&obj, &light, &Point::new(0., 0., 500.), &eyev, &normalv, false);
+
+        assert_eq!(near.red(), 1.9);
+        assert_eq!(far.red(), 1.9);
+    }
+
+    #[test]
+    fn test_light_kind_from_json() {
+        let point: Light = serde_json::from_str(
+            r#"{ "position": { "x": 0.0, "y": 10.0, "z": 0.0 }, "intensity": { "r": 1.0, "g": 1.0, "b": 1.0 } }"#,
+        )
+        .unwrap();
+        assert!(matches!(point.kind, LightKind::Point));
+
+        let sun: Light = serde_json::from_str(
+            r#"{ "intensity": { "r": 1.0, "g": 0.9, "b": 0.8 },
+                 "kind": { "Directional": { "direction": { "x": 0.0, "y": -1.0, "z": 0.0 } } } }"#,
+        )
+        .unwrap();
+        assert_eq!(sun.direction_from(&Point::new(1., 2., 3.)), Vector::new(0., 1., 0.));
+    }
+
+    #[test]
+    fn test_spot_light_falloff() {
+        let light = Light::spot(
+            Point::new(0., 10., 0.),
+            Vector::new(0., -1., 0.),
+            20.,
+            30.,
+            Color::new(1., 1., 1.),
+        );
+
+        // straight below, inside the inner cone
+        assert_eq!(light.falloff(&Point::new(0., 0., 0.)), 1.);
+        // 45 degrees away, outside the outer cone
+        assert_eq!(light.falloff(&Point::new(10., 0., 0.)), 0.);
+
+        // 25 degrees away, between both cones
+        let x = 10. * 25f32.to_radians().tan();
+        let falloff = light.falloff(&Point::new(x, 0., 0.));
+        assert!(falloff > 0. && falloff < 1.);
+
+        // the falloff goes down when going away from the axis
+        let closer = light.falloff(&Point::new(x * 0.9, 0., 0.));
+        assert!(closer > falloff);
+    }
+
+    #[test]
+    fn test_lighting_outside_spot_is_ambient() {
+        let obj = Object::new(Shape::Sphere(Sphere::new(1.))).material(Material::new());
+        let eyev = Vector::new(0., 1., 0.);
+        let normalv = Vector::new(0., 1., 0.);
+
+        let light = Light::spot(
+            Point::new(0., 10., 0.),
+            Vector::new(0., -1., 0.),
+            10.,
+            15.,
+            Color::new(1., 1., 1.),
+        );
+
+        let lit = World::lighting(&obj, &light, &Point::new(0., 0., 0.), &eyev, &normalv, false);
+        let dark = World::lighting(&obj, &light, &Point::new(8., 0., 0.), &eyev, &normalv, false);
+
+        assert!(lit.red() > 1.);
+        assert_eq!(dark.red(), 0.1);
+    }
+
+    #[test]
+    fn test_rectangle_light_samples() {
+        let light = Light::area(
+            Point::new(0., 5., 0.),
+            AreaShape::Rectangle {
+                u: Vector::new(2., 0., 0.),
+                v: Vector::new(0., 0., 1.),
+            },
+            10,
+            Color::new(1., 1., 1.),
+        );
+        let mut rng = Rng::new(7);
+
+        let samples = light.sample_positions(&Point::new(0., 0., 0.), &mut rng);
+
+        // rounded up to a 4 by 4 grid
+        assert_eq!(samples.len(), 16);
+        for sample in &samples {
+            assert_eq!(sample.y, 5.);
+            assert!(sample.x.abs() <= 1. && sample.z.abs() <= 0.5);
+        }
+        // one sample per stratum along each edge
+        assert!(samples.iter().any(|s| s.x < -0.5) && samples.iter().any(|s| s.x > 0.5));
+    }
+
+    #[test]
+    fn test_disk_and_sphere_light_samples() {
+        let disk = Light::area(
+            Point::new(0., 5., 0.),
+            AreaShape::Disk {
+                normal: Vector::new(0., -1., 0.),
+                radius: 2.,
+            },
+            9,
+            Color::new(1., 1., 1.),
+        );
+        let sphere = Light::area(
+            Point::new(0., 5., 0.),
+            AreaShape::Sphere { radius: 1. },
+            9,
+            Color::new(1., 1., 1.),
+        );
+        let mut rng = Rng::new(3);
+        let point = Point::new(0., 0., 0.);
+
+        for sample in disk.sample_positions(&point, &mut rng) {
+            assert!((sample.y - 5.).abs() < 1e-5);
+            assert!((sample - disk.position).magnitude() <= 2.0001);
+        }
+
+        for sample in sphere.sample_positions(&point, &mut rng) {
+            assert!(((sample - sphere.position).magnitude() - 1.).abs() < 1e-4);
+            // on the side facing the point
+            assert!(sample.y <= 5.);
+        }
+    }
+
+    #[test]
+    fn test_lighting_with_partial_visibility() {
+        let obj = Object::new(Shape::Sphere(Sphere::new(1.))).material(Material::new());
+        let position = Point::new(0., 0., 0.);
+        let eyev = Vector::new(0., 0., -1.);
+        let normalv = Vector::new(0., 0., -1.);
+        let light = Light::new(Point::new(0., 0., -10.), Color::new(1., 1., 1.));
+
+        let half_red = Color::new(0.5, 0., 0.);
+
+        let full =
+            World::lighting_with_visibility(&obj, &light, &position, &eyev, &normalv, Color::WHITE);
+        let half =
+            World::lighting_with_visibility(&obj, &light, &position, &eyev, &normalv, half_red);
+        let none =
+            World::lighting_with_visibility(&obj, &light, &position, &eyev, &normalv, Color::BLACK);
+
+        assert_eq!(full.red(), 1.9);
+        assert_eq!(half.red(), 1.);
+        assert_eq!(half.green(), 0.1);
+        assert_eq!(none.red(), 0.1);
+    }
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng::from_point(&Point::new(1., 2., 3.));
+        let mut b = Rng::from_point(&Point::new(1., 2., 3.));
+
+        for _ in 0..100 {
+            let x = a.next_f32();
+            assert_eq!(x, b.next_f32());
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_inverse_square_attenuation_by_default() {
+        let light = Light::new(Point::new(0., 0., 0.), Color::new(1., 1., 1.));
+
+        assert_eq!(light.attenuation_at(&Point::new(0., 2., 0.)), 0.25);
+        assert_eq!(light.attenuation_at(&Point::new(0., 0., 10.)), 0.01);
+    }
+
+    #[test]
+    fn test_configurable_attenuation() {
+        let light = Light::new(Point::new(0., 0., 0.), Color::new(1., 1., 1.))
+            .attenuation(Attenuation::new(1., 0.5, 0.));
+
+        assert_eq!(light.attenuation_at(&Point::new(0., 2., 0.)), 0.5);
+
+        let constant = light.clone().attenuation(Attenuation::none());
+        assert_eq!(constant.attenuation_at(&Point::new(0., 100., 0.)), 1.);
+
+        let sun = Light::directional(Vector::new(0., -1., 0.), Color::new(1., 1., 1.))
+            .attenuation(Attenuation::default());
+        assert_eq!(sun.attenuation_at(&Point::new(0., 100., 0.)), 1.);
+    }
+
+    #[test]
+    fn test_light_model_from_json() {
+        // the maps without it keep rendering as they always did
+        let legacy: World = serde_json::from_str(r#"{ "objects": [], "lights": [] }"#).unwrap();
+        assert_eq!(legacy.light_model, LightModel::Legacy);
+
+        let physical: World =
+            serde_json::from_str(r#"{ "light_model": "Physical", "objects": [], "lights": [] }"#)
+                .unwrap();
+        assert_eq!(physical.light_model, LightModel::Physical);
+    }
+
+    #[test]
+    fn test_opaque_object_blocks_the_light() {
+        let mut world = World::new();
+        world.add_object(Object::new(Shape::Sphere(Sphere::new(1.))));
+
+        let point = Point::new(0., -5., 0.);
+        let up = Vector::new(0., 1., 0.);
+
+        assert!(world.transmittance(&point, &up, 10.).is_black());
+        // the light is before the sphere
+        assert_eq!(world.transmittance(&point, &up, 3.).red(), 1.);
+    }
+
+    #[test]
+    fn test_red_glass_casts_a_red_shadow() {
+        let mut world = World::new();
+        let mut glass = Material::new().color(Color::new(1., 0., 0.));
+        glass.transparency = 0.9;
+        world.add_object(Object::new(Shape::Sphere(Sphere::new(1.))).material(glass));
+
+        let tint = world.transmittance(&Point::new(0., -5., 0.), &Vector::new(0., 1., 0.), 10.);
+
+        // crossed twice, in and out
+        assert!((tint.red() - 0.81).abs() < 1e-5);
+        assert_eq!(tint.green(), 0.);
+        assert_eq!(tint.blue(), 0.);
+    }
+
+    #[test]
+    fn test_transparent_then_opaque() {
+        let mut world = World::new();
+        let mut glass = Material::new();
+        glass.transparency = 0.5;
+
+        let mut front = Object::new(Shape::Sphere(Sphere::new(1.))).material(glass);
+        front.position = Point::new(0., -2., 0.);
+        front.update();
+        let mut back = Object::new(Shape::Sphere(Sphere::new(1.)));
+        back.position = Point::new(0., 2., 0.);
+        back.update();
+        world.add_object(front);
+        world.add_object(back);
+
+        let point = Point::new(0., -5., 0.);
+        let up = Vector::new(0., 1., 0.);
+
+        assert!((world.transmittance(&point, &up, 5.).red() - 0.25).abs() < 1e-5);
+        assert!(world.transmittance(&point, &up, 10.).is_black());
+    }
+
+    #[test]
+    fn test_physical_ambient_doesnt_fade() {
+        let mut world = World::new();
+        world.light_model = LightModel::Physical;
+        let red = Material::new().color(Color::new(1., 0., 0.));
+        world.add_object(Object::new(Shape::Sphere(Sphere::new(1.))).material(red));
+        // far behind the camera, almost no direct light left
+        world.add_light(Light::new(Point::new(0., 0., 1000.), Color::WHITE));
+
+        let canvas = Canvas::new(NewCanvas {
+            width: 1,
+            height: 1,
+        });
+        let ray = Ray::new(Point::new(0., 0., 5.), Vector::new(0., 0., -1.));
+        let renderer = Renderer::headless(canvas, world.clone());
+        let (color, _) = renderer.trace(&ray, &mut Rng::new(0));
+        assert!((color.red() - 0.1).abs() < 1e-3);
+        assert!(color.green() < 1e-5);
+
+        // counted once, not once per light
+        world.add_light(Light::new(Point::new(0., 1000., 0.), Color::WHITE));
+        let canvas = Canvas::new(NewCanvas {
+            width: 1,
+            height: 1,
+        });
+        let renderer = Renderer::headless(canvas, world);
+        let (color, _) = renderer.trace(&ray, &mut Rng::new(0));
+        assert!((color.red() - 0.1).abs() < 1e-3);
+    }
 }
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use new_rt::{Color, Material, Object, Shape, Sphere};
+    use new_rt::{
+        Color, Ggx, Light, Material, Object, Pbr, Point, Rng, Shape, Sphere, Vector, World,
+    };
 
     #[test]
     fn test_setup_material() {
@@ -16,6 +18,8 @@ mod tests {
             reflective: 0.,
             refractive_index: 1.,
             transparency: 0.,
+            pbr: None,
+            emission: None,
         };
 
         assert_eq!(material.color.red(), 1.);
@@ -52,4 +56,107 @@ mod tests {
         assert_eq!(sphere.material.specular, 0.9);
         assert_eq!(sphere.material.shininess, 200.);
     }
+
+    #[test]
+    fn test_pbr_from_json() {
+        let material: Material = serde_json::from_str(
+            r#"{
+                "color": { "r": 1.0, "g": 0.8, "b": 0.3 },
+                "shininess": 50.0, "ambient": 0.1, "diffuse": 0.9, "specular": 0.9,
+                "reflective": 0.0, "refractive_index": 1.0, "pattern": null, "transparency": 0.0,
+                "pbr": { "metallic": 1.0 }
+            }"#,
+        )
+        .unwrap();
+
+        let pbr = material.pbr.unwrap();
+        assert_eq!(pbr.metallic, 1.);
+        assert_eq!(pbr.roughness, 0.5);
+        assert_eq!(pbr.reflectance, 0.04);
+
+        // the maps written before stay phong
+        assert!(Material::new().pbr.is_none());
+    }
+
+    #[test]
+    fn test_pbr_from_phong() {
+        let material = Material::new()
+            .shininess(200.)
+            .specular(0.5)
+            .reflective(1.)
+            .to_pbr();
+        let pbr = material.pbr.unwrap();
+
+        assert_eq!(pbr.metallic, 1.);
+        assert!((pbr.roughness - 0.315).abs() < 1e-3);
+        assert!((pbr.reflectance - 0.04).abs() < 1e-6);
+
+        // wider highlight, rougher surface
+        let rough = Pbr::from_phong(&Material::new().shininess(10.));
+        assert!(rough.roughness > pbr.roughness);
+    }
+
+    #[test]
+    fn test_ggx_metal_reflects_its_color() {
+        let gold = Ggx::new(Color::new(1., 0.8, 0.3), &Pbr::new(1., 0.3));
+        let n = Vector::new(0., 1., 0.);
+
+        let f = gold.eval(&n, &n, &n);
+        assert!(f.red() > f.green() && f.green() > f.blue());
+
+        // no diffuse layer: nothing away from the mirror direction
+        let wi = Vector::new(1., 1., 0.).normalize();
+        let wo = Vector::new(1., 1., 0.).normalize();
+        assert!(gold.eval(&wi, &wo, &n).red() < 0.01 * f.red());
+
+        // light doesn't care which way it goes
+        let wo = Vector::new(-0.3, 1., 0.2).normalize();
+        let there = gold.eval(&wi, &wo, &n);
+        let back = gold.eval(&wo, &wi, &n);
+        assert!((there.red() - back.red()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ggx_does_not_create_energy() {
+        let n = Vector::new(0., 1., 0.);
+        let wo = Vector::new(0.5, 1., 0.).normalize();
+        let mut rng = Rng::new(11);
+
+        for (metallic, roughness) in [(0., 0.2), (0., 0.8), (1., 0.1), (1., 0.6)] {
+            let brdf = Ggx::new(Color::WHITE, &Pbr::new(metallic, roughness));
+            let mut albedo = 0.;
+
+            for _ in 0..4000 {
+                let (u, v, w) = (rng.next_f32(), rng.next_f32(), rng.next_f32());
+                if let Some(wi) = brdf.sample(&wo, &n, u, v, w) {
+                    let pdf = brdf.pdf(&wi, &wo, &n);
+                    albedo += brdf.eval(&wi, &wo, &n).red() * wi.dot(&n) / pdf;
+                }
+            }
+            albedo /= 4000.;
+
+            assert!(albedo < 1.05, "{} {} -> {}", metallic, roughness, albedo);
+            assert!(albedo > 0.7, "{} {} -> {}", metallic, roughness, albedo);
+        }
+    }
+
+    #[test]
+    fn test_pbr_in_direct_lighting() {
+        let material = Material::new()
+            .color(Color::new(1., 0., 0.))
+            .ambient(0.)
+            .pbr(Pbr::new(0., 1.));
+        let obj = Object::new(Shape::Sphere(Sphere::new(1.))).material(material);
+        let light = Light::new(Point::new(0., 0., -10.), Color::new(1., 1., 1.));
+        let n = Vector::new(0., 0., -1.);
+
+        let color = World::lighting(&obj, &light, &Point::new(0., 0., 0.), &n, &n, false);
+
+        // red diffuse layer with a small white reflection on top
+        assert!(color.red() > 0.8);
+        assert!(color.green() > 0. && color.green() < 0.1);
+
+        let shadowed = World::lighting(&obj, &light, &Point::new(0., 0., 0.), &n, &n, true);
+        assert_eq!(shadowed.red(), 0.);
+    }
 }
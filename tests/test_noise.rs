@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Axis, Color, ColorStop, Material, Object, Pattern, Point, Procedural, Shape, Sphere,
+        Texture, fbm, perlin, turbulence,
+    };
+
+    #[test]
+    fn test_perlin_is_zero_on_integer_points() {
+        assert_eq!(perlin(&Point::new(0., 0., 0.)), 0.);
+        assert_eq!(perlin(&Point::new(1., 2., 3.)), 0.);
+        assert_eq!(perlin(&Point::new(-4., 7., -2.)), 0.);
+    }
+
+    #[test]
+    fn test_perlin_is_deterministic_and_bounded() {
+        for i in 0..200 {
+            let p = Point::new(i as f32 * 0.37, i as f32 * -0.21, i as f32 * 0.13);
+            let n = perlin(&p);
+
+            assert_eq!(n, perlin(&p));
+            assert!((-1.0..=1.0).contains(&n));
+        }
+    }
+
+    #[test]
+    fn test_fbm_and_turbulence_ranges() {
+        for i in 0..200 {
+            let p = Point::new(i as f32 * 0.53, 1.5, i as f32 * 0.29);
+
+            assert!((-1.0..=1.0).contains(&fbm(&p, 5, 2.0, 0.5)));
+            assert!((0.0..=1.0).contains(&turbulence(&p, 5, 2.0, 0.5)));
+        }
+    }
+
+    #[test]
+    fn test_ramp_interpolates_between_stops() {
+        let red = Color::new(1., 0., 0.);
+        let green = Color::new(0., 1., 0.);
+        let blue = Color::new(0., 0., 1.);
+
+        let procedural = Procedural::new(Texture::Marble).ramp(vec![
+            ColorStop::new(0., red),
+            ColorStop::new(0.5, green),
+            ColorStop::new(1., blue),
+        ]);
+
+        let color = procedural.ramp_at(0.25, &Color::BLACK, &Color::BLACK);
+        assert_eq!(color.r, 0.5);
+        assert_eq!(color.g, 0.5);
+        assert_eq!(color.b, 0.);
+
+        let color = procedural.ramp_at(1., &Color::BLACK, &Color::BLACK);
+        assert_eq!(color.b, 1.);
+    }
+
+    #[test]
+    fn test_ramp_defaults_to_pattern_colors() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let procedural = Procedural::new(Texture::Clouds);
+
+        let color = procedural.ramp_at(0.75, &black, &white);
+        assert_eq!(color.r, 0.75);
+        assert_eq!(color.g, 0.75);
+        assert_eq!(color.b, 0.75);
+    }
+
+    #[test]
+    fn test_procedural_pattern_stays_between_colors() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let pattern = Pattern::new(black, white, Axis::X, false)
+            .procedural(Procedural::new(Texture::Wood).frequency(3.));
+        let mut material = Material::new();
+        material.pattern = Some(pattern);
+
+        let obj = Object::new(Shape::Sphere(Sphere::new(1.))).material(material);
+        let pattern = obj.material.pattern.clone().unwrap();
+
+        for i in 0..50 {
+            let p = Point::new(i as f32 * 0.11, 0.3, i as f32 * -0.07);
+            let color = pattern.stripe_at_object(&obj, &p);
+
+            assert!((0.0..=1.0).contains(&color.r));
+            assert_eq!(color.r, color.g);
+            assert_eq!(color.g, color.b);
+        }
+    }
+
+    #[test]
+    fn test_procedural_from_json_uses_defaults() {
+        let json = r#"{ "texture": "Marble", "frequency": 2.0 }"#;
+        let procedural: Procedural = serde_json::from_str(json).unwrap();
+
+        assert_eq!(procedural.texture, Texture::Marble);
+        assert_eq!(procedural.frequency, 2.);
+        assert_eq!(procedural.octaves, 4);
+        assert!(procedural.ramp.is_empty());
+    }
+}
@@ -1,9 +1,37 @@
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use new_rt::{
-        Axis, Color, Light, Material, Matrix, Object, Pattern, Point, Shape, Sphere, Transform,
-        Vector, World,
+        Axis, Checker, Color, Combinator, Light, Material, Matrix, Object, Paint, Pattern,
+        PatternKind, Perturbation, Point, Shape, Sphere, Transform, Vector, World, create_pattern,
+        register_pattern, register_pattern_type,
     };
+    use serde::{Deserialize, Serialize};
+
+    // polka dots, one per unit cube
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Dots {
+        radius: f32,
+    }
+
+    impl PatternKind for Dots {
+        fn name(&self) -> &str {
+            "dots"
+        }
+
+        fn weight_at(&self, point: &Point) -> f32 {
+            let dx = point.x - point.x.floor() - 0.5;
+            let dy = point.y - point.y.floor() - 0.5;
+            let dz = point.z - point.z.floor() - 0.5;
+
+            if (dx * dx + dy * dy + dz * dz).sqrt() < self.radius { 1. } else { 0. }
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::to_value(self).unwrap()
+        }
+    }
 
     #[test]
     fn test_creating_stripe_pattern() {
@@ -11,14 +39,15 @@ mod tests {
         let black = Color::new(0., 0., 0.);
 
         let pattern = Pattern::new(white, black, Axis::X, false);
+        let origin = Point::new(0., 0., 0.);
 
-        assert_eq!(white.r, pattern.a.r);
-        assert_eq!(white.g, pattern.a.g);
-        assert_eq!(white.b, pattern.a.b);
+        assert_eq!(white.r, pattern.a.color_at(&origin).r);
+        assert_eq!(white.g, pattern.a.color_at(&origin).g);
+        assert_eq!(white.b, pattern.a.color_at(&origin).b);
 
-        assert_eq!(black.r, pattern.b.r);
-        assert_eq!(black.g, pattern.b.g);
-        assert_eq!(black.b, pattern.b.b);
+        assert_eq!(black.r, pattern.b.color_at(&origin).r);
+        assert_eq!(black.g, pattern.b.color_at(&origin).g);
+        assert_eq!(black.b, pattern.b.color_at(&origin).b);
     }
 
     #[test]
@@ -79,10 +108,7 @@ mod tests {
         let eyev = Vector::new(0., 0., -1.);
         let normalv = Vector::new(0., 0., -1.);
 
-        let light = Light {
-            position: Point::new(0., 0., -10.),
-            intensity: Color::new(1., 1., 1.),
-        };
+        let light = Light::new(Point::new(0., 0., -10.), Color::new(1., 1., 1.));
 
         let p1 = Point::new(0.9, 0., 0.);
         let c1 = World::lighting(&obj.clone(), &light, &p1, &eyev, &normalv, false);
@@ -366,4 +392,182 @@ mod tests {
         assert_eq!(color.g, 0.);
         assert_eq!(color.b, 0.);
     }
+
+    #[test]
+    fn test_checker_of_stripes() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+        let red = Color::new(1., 0., 0.);
+
+        let stripes = Pattern::new(white, black, Axis::X, false);
+        let pattern = Pattern::new(stripes, red, Axis::XYZ, false);
+
+        // first checker cell follows the stripes
+        let color = pattern.color_at(&Point::new(0.5, 0.5, 0.5));
+        assert_eq!(color.r, 1.);
+        assert_eq!(color.g, 1.);
+        let color = pattern.color_at(&Point::new(-0.5, 0.5, -0.5));
+        assert_eq!(color.r, 0.);
+        assert_eq!(color.g, 0.);
+
+        // the other one is plain red
+        let color = pattern.color_at(&Point::new(1.5, 0.5, 0.5));
+        assert_eq!(color.r, 1.);
+        assert_eq!(color.g, 0.);
+    }
+
+    #[test]
+    fn test_nested_pattern_uses_its_own_transformation() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let mut stripes = Pattern::new(white, black, Axis::X, false);
+        stripes.scale(Vector::new(0.5, 0.5, 0.5));
+        let pattern = Pattern::new(stripes, black, Axis::XYZ, false);
+
+        let color = pattern.color_at(&Point::new(0.25, 0., 0.));
+        assert_eq!(color.r, 1.);
+        let color = pattern.color_at(&Point::new(0.75, 0., 0.));
+        assert_eq!(color.r, 0.);
+    }
+
+    #[test]
+    fn test_mix_combinator() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let pattern = Pattern::new(white, black, Axis::X, false).combinator(Combinator::Mix(0.25));
+
+        let color = pattern.color_at(&Point::new(1.5, 0., 0.));
+        assert_eq!(color.r, 0.75);
+        assert_eq!(color.g, 0.75);
+        assert_eq!(color.b, 0.75);
+    }
+
+    #[test]
+    fn test_mask_combinator() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+        let red = Color::new(1., 0., 0.);
+        let blue = Color::new(0., 0., 1.);
+
+        let mask = Pattern::new(black, white, Axis::X, false);
+        let pattern =
+            Pattern::new(red, blue, Axis::X, false).combinator(Combinator::Mask(Box::new(mask)));
+
+        let color = pattern.color_at(&Point::new(0.5, 0., 0.));
+        assert_eq!(color.r, 1.);
+        assert_eq!(color.b, 0.);
+        let color = pattern.color_at(&Point::new(1.5, 0., 0.));
+        assert_eq!(color.r, 0.);
+        assert_eq!(color.b, 1.);
+    }
+
+    #[test]
+    fn test_perturbation_moves_the_lookup_point() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let still = Pattern::new(white, black, Axis::X, false).perturbation(Perturbation::new(0.));
+        let color = still.color_at(&Point::new(0.9, 0.3, 0.2));
+        assert_eq!(color.r, 1.);
+
+        let wavy = Pattern::new(white, black, Axis::X, false).perturbation(Perturbation::new(2.));
+        let changed = (0..20)
+            .map(|i| Point::new(0.05 * i as f32, 0.37, 0.71))
+            .any(|p| wavy.color_at(&p).r != still.color_at(&p).r);
+        assert!(changed);
+    }
+
+    #[test]
+    fn test_pattern_tree_from_json() {
+        let identity = r#"{ "data": [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]] }"#;
+        let transform = format!(
+            r#""local_to_world": {identity}, "world_to_local": {identity},
+            "scale": {{ "x": 1.0, "y": 1.0, "z": 1.0 }}, "position": {{ "x": 0.0, "y": 0.0, "z": 0.0 }},
+            "pitch": 0.0, "yaw": 0.0, "roll": 0.0"#
+        );
+        let json = format!(
+            r#"{{
+                "a": {{ "a": {{ "r": 1.0, "g": 1.0, "b": 1.0 }}, "b": {{ "r": 0.0, "g": 0.0, "b": 0.0 }},
+                        "axis": "X", "blending": false, {transform} }},
+                "b": {{ "r": 1.0, "g": 0.0, "b": 0.0 }},
+                "axis": "XYZ", "blending": false, {transform}
+            }}"#
+        );
+
+        let pattern: Pattern = serde_json::from_str(&json).unwrap();
+
+        assert!(matches!(pattern.a, Paint::Pattern(_)));
+        assert!(matches!(pattern.b, Paint::Color(_)));
+        assert_eq!(pattern.color_at(&Point::new(1.5, 0.5, 0.5)).g, 0.);
+    }
+
+    #[test]
+    fn test_kind_replaces_the_axis() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let pattern = Pattern::new(white, black, Axis::X, false).kind(Arc::new(Checker));
+
+        assert_eq!(pattern.color_at(&Point::new(1.5, 0.5, 0.5)).r, 0.);
+        assert_eq!(pattern.color_at(&Point::new(1.5, 1.5, 0.5)).r, 1.);
+    }
+
+    #[test]
+    fn test_builtin_kinds_are_registered() {
+        let ring = create_pattern(&serde_json::json!({ "type": "ring", "plane": "XY" })).unwrap();
+        assert_eq!(ring.name(), "ring");
+        assert_eq!(ring.weight_at(&Point::new(0., 1.5, 0.)), 1.);
+        assert_eq!(ring.weight_at(&Point::new(0., 0., 1.5)), 0.);
+
+        let marble = create_pattern(&serde_json::json!({ "type": "marble", "octaves": 2 })).unwrap();
+        assert_eq!(marble.name(), "marble");
+
+        assert!(create_pattern(&serde_json::json!({ "type": "nope" })).is_err());
+        assert!(create_pattern(&serde_json::json!({ "radius": 1.0 })).is_err());
+    }
+
+    #[test]
+    fn test_factory_can_use_the_registry() {
+        // an alias of checker, built through the registry from inside a factory
+        register_pattern("tiles", |_| {
+            create_pattern(&serde_json::json!({ "type": "checker" }))
+        });
+
+        let tiles = create_pattern(&serde_json::json!({ "type": "tiles" })).unwrap();
+        assert_eq!(tiles.name(), "checker");
+    }
+
+    #[test]
+    fn test_two_and_three_colors_stripes() {
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+        let pattern = Pattern::new(white, black, Axis::XY, false);
+
+        assert_eq!(pattern.stripe_two_colors(&0.5, &0.).r, 1.);
+        assert_eq!(pattern.stripe_two_colors(&0., &1.5).r, 0.);
+        assert_eq!(pattern.stripe_three_colors(&Point::new(0.5, 0.5, 0.5)).r, 1.);
+        assert_eq!(pattern.stripe_three_colors(&Point::new(1.5, 0.5, 0.5)).r, 0.);
+    }
+
+    #[test]
+    fn test_custom_kind_from_scene_json() {
+        register_pattern_type::<Dots>("dots");
+
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+
+        let mut value = serde_json::to_value(Pattern::new(white, black, Axis::X, false)).unwrap();
+        value["kind"] = serde_json::json!({ "type": "dots", "radius": 0.25 });
+
+        let pattern: Pattern = serde_json::from_value(value).unwrap();
+        assert_eq!(pattern.color_at(&Point::new(0.5, 0.5, 0.5)).r, 0.);
+        assert_eq!(pattern.color_at(&Point::new(0.1, 0.1, 0.1)).r, 1.);
+
+        // saved back with its tag and parameters
+        let saved = serde_json::to_value(&pattern).unwrap();
+        assert_eq!(saved["kind"]["type"], "dots");
+        assert_eq!(saved["kind"]["radius"], 0.25);
+    }
 }
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use new_rt::{Point, Ray, Vector};
+    use new_rt::{Matrix, Point, Ray, Vector};
 
     #[test]
     fn test_position_ray_0() {
@@ -34,4 +34,15 @@ mod tests {
         assert_eq!(result.y, 3.0);
         assert_eq!(result.z, 4.0);
     }
+
+    #[test]
+    fn test_ray_time() {
+        let ray = Ray::new(Point::new(0., 0., 0.), Vector::new(0., 0., 1.));
+        assert_eq!(ray.time, 0.);
+
+        // a moved ray still happens at the same time
+        let ray = Matrix::translation(Vector::new(1., 2., 3.)) * ray.at_time(0.25);
+        assert_eq!(ray.origin, Point::new(1., 2., 3.));
+        assert_eq!(ray.time, 0.25);
+    }
 }
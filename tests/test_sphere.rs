@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use new_rt::{Object, Point, Ray, Shape, Sphere, Transform, Vector};
+    use new_rt::{Motion, Object, Point, Ray, Shape, Sphere, Transform, Vector};
 
     #[test]
     fn test_sphere_intersection() {
@@ -89,4 +89,68 @@ mod tests {
         let result = obj.intersect(&ray, 1.).is_none();
         assert_eq!(result, true);
     }
+
+    fn moving_sphere() -> Object {
+        let mut obj = Object::new(Shape::Sphere(Sphere::new(1.)))
+            .motion(Motion::new().position(Point::new(4., 0., 0.)));
+        obj.update();
+
+        obj
+    }
+
+    #[test]
+    fn test_moving_sphere_is_where_it_is_at_the_ray_time() {
+        let obj = moving_sphere();
+        let ray = Ray::new(Point::new(2., 0., -5.), Vector::new(0., 0., 1.));
+
+        // starts at the origin and goes to x = 4 when the shutter closes
+        assert!(obj.intersect(&ray, 1.).is_none());
+        assert!(obj.intersect(&ray.at_time(1.), 1.).is_none());
+
+        let hit = obj.intersect(&ray.at_time(0.5), 1.).unwrap();
+        assert_eq!(hit.t, 4.);
+        assert_eq!(hit.point, Point::new(2., 0., -1.));
+        assert_eq!(hit.time, 0.5);
+
+        // the objects that don't move are the same at every time
+        let still = Object::new(Shape::Sphere(Sphere::new(1.)));
+        let ray = Ray::new(Point::new(0., 0., -5.), Vector::new(0., 0., 1.));
+        assert_eq!(still.intersect(&ray.at_time(0.7), 1.).unwrap().t, 4.);
+    }
+
+    #[test]
+    fn test_bounding_sphere_covers_the_motion() {
+        let (center, radius) = moving_sphere().bounding_sphere().unwrap();
+        assert_eq!(center, Point::new(2., 0., 0.));
+        assert!(radius >= 3.);
+    }
+
+    #[test]
+    fn test_inverse_placement_during_the_motion() {
+        let motion = Motion::new()
+            .position(Point::new(4., 0., 0.))
+            .rotation(0.2, 1., 0.)
+            .scale(Vector::new(2., 1., 0.5));
+        let mut obj = Object::new(Shape::Sphere(Sphere::new(1.))).motion(motion);
+        obj.update();
+
+        for time in [0., 0.3, 1.] {
+            let point = Point::new(0.5, -1., 2.);
+            let back = obj.inverse_transform_at(time).unwrap() * (obj.transform_at(time) * point);
+            assert!((back - point).magnitude() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_motion_from_json() {
+        let motion: Motion = serde_json::from_str(r#"{ "yaw": 1.57 }"#).unwrap();
+        let mut obj = Object::new(Shape::Sphere(Sphere::new(1.))).motion(motion);
+        obj.translate(Vector::new(0., 1., 0.));
+
+        // only turns, the position stays
+        let start = obj.transform_at(0.) * Point::new(0., 0., 0.);
+        let end = obj.transform_at(1.) * Point::new(0., 0., 0.);
+        assert_eq!(start, Point::new(0., 1., 0.));
+        assert_eq!(end, Point::new(0., 1., 0.));
+    }
 }
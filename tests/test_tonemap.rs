@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use new_rt::{
+        Canvas, Color, DisplaySettings, NewCanvas, Options, RenderSettings, ToneMapping,
+        linear_to_srgb, srgb_to_linear,
+    };
+
+    #[test]
+    fn test_srgb_curve() {
+        assert_eq!(linear_to_srgb(0.), 0.);
+        assert!((linear_to_srgb(1.) - 1.).abs() < 1e-6);
+        // the midtones are brighter once encoded
+        assert!((linear_to_srgb(0.2140) - 0.5).abs() < 1e-3);
+        assert!((srgb_to_linear(linear_to_srgb(0.37)) - 0.37).abs() < 1e-5);
+
+        let display = DisplaySettings {
+            srgb: true,
+            ..DisplaySettings::default()
+        };
+        assert_eq!(display.encode(Color::new(0.22, 0., 1.)), [129, 0, 255]);
+        assert_eq!(display.pack(Color::new(1., 0., 0.)), 0xff0000);
+
+        // off unless asked for, the linear values truncated like the first renders
+        let linear = DisplaySettings::default();
+        assert!(!linear.srgb);
+        assert_eq!(linear.encode(Color::new(0.5, 0., 0.))[0], 127);
+        let pixel: u32 = Color::new(0.5, 0.2, 1.).into();
+        assert_eq!(linear.pack(Color::new(0.5, 0.2, 1.)), pixel);
+    }
+
+    #[test]
+    fn test_tone_mapping_keeps_the_highlights() {
+        for tone_mapping in [ToneMapping::Reinhard, ToneMapping::Aces] {
+            // always in [0, 1], growing, bright values stay apart
+            assert_eq!(tone_mapping.map(0.), 0.);
+            assert!(tone_mapping.map(2.) < tone_mapping.map(4.));
+            assert!(tone_mapping.map(100.) <= 1.);
+        }
+        assert_eq!(ToneMapping::Clamp.map(2.), ToneMapping::Clamp.map(4.));
+        assert_eq!(ToneMapping::Reinhard.map(1.), 0.5);
+        // filmic contrast, darker shadows
+        assert!(ToneMapping::Aces.map(0.05) < 0.05);
+        assert_eq!(ToneMapping::Clamp.map(-1.), 0.);
+    }
+
+    #[test]
+    fn test_exposure() {
+        let display = DisplaySettings {
+            exposure: 1.,
+            srgb: false,
+            ..DisplaySettings::default()
+        };
+        assert_eq!(display.map(Color::new(0.25, 0.5, 2.)).red(), 0.5);
+        assert_eq!(display.map(Color::new(0.25, 0.5, 2.)).blue(), 1.);
+
+        // the file and the window get the same bytes
+        let mut canvas = Canvas::new(NewCanvas {
+            width: 1,
+            height: 1,
+        });
+        canvas.write(0, 0, Color::new(0.25, 0., 0.));
+        assert_eq!(canvas.pixels(&display), vec![0x7f0000]);
+        assert_eq!(canvas.to_ppm(&display), "P3\n1 1\n255\n127 0 0 \n");
+    }
+
+    #[test]
+    fn test_display_from_scene_and_command_line() {
+        let settings: RenderSettings =
+            serde_json::from_str(r#"{ "display": { "exposure": -0.5, "tone_mapping": "Aces" } }"#)
+                .unwrap();
+        assert_eq!(settings.display.exposure, -0.5);
+        assert_eq!(settings.display.tone_mapping, ToneMapping::Aces);
+        assert!(!settings.display.srgb);
+
+        let args: Vec<String> = ["a.json", "--exposure", "1.5", "--tone-map", "reinhard"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut settings = settings;
+        Options::parse(&args).unwrap().apply(&mut settings);
+        assert_eq!(settings.display.exposure, 1.5);
+        assert_eq!(settings.display.tone_mapping, ToneMapping::Reinhard);
+
+        assert!("gamma".parse::<ToneMapping>().is_err());
+    }
+}